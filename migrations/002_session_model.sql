@@ -0,0 +1,2 @@
+ALTER TABLE sessions ADD COLUMN model_id TEXT;
+ALTER TABLE sessions ADD COLUMN provider TEXT;
@@ -20,6 +20,40 @@ pub struct Agent {
     system_prompt: String,
     working_dir: std::path::PathBuf,
     team_state: Arc<RwLock<Option<TeamState>>>,
+    agent_config: crate::core::config::AgentConfig,
+    tools_config: crate::core::config::ToolsConfig,
+    tool_limiter: Arc<ToolLimiter>,
+    /// Cheap model used for auxiliary requests like tool result
+    /// summarization; absent when no fast model could be built
+    fast_provider: Option<Arc<dyn Provider>>,
+    /// Snapshot of the message list the most recent run assembled,
+    /// including trimming, compaction, and tool turns; see
+    /// [`last_run_messages`](Self::last_run_messages)
+    last_run_messages: Arc<std::sync::Mutex<Vec<Message>>>,
+}
+
+/// Per-tool concurrency limiter. Tools with a configured limit share a
+/// semaphore; calls beyond the limit queue until a slot frees up.
+pub struct ToolLimiter {
+    semaphores: std::collections::HashMap<String, Arc<tokio::sync::Semaphore>>,
+}
+
+impl ToolLimiter {
+    pub fn new(limits: &std::collections::HashMap<String, usize>) -> Self {
+        let semaphores = limits
+            .iter()
+            .filter(|(_, &limit)| limit > 0)
+            .map(|(name, &limit)| (name.clone(), Arc::new(tokio::sync::Semaphore::new(limit))))
+            .collect();
+        Self { semaphores }
+    }
+
+    /// Wait for a slot for the named tool. Returns `None` immediately for
+    /// tools without a configured limit.
+    async fn acquire(&self, tool_name: &str) -> Option<tokio::sync::OwnedSemaphorePermit> {
+        let sem = self.semaphores.get(tool_name)?;
+        sem.clone().acquire_owned().await.ok()
+    }
 }
 
 impl Agent {
@@ -38,7 +72,54 @@ impl Agent {
             system_prompt,
             working_dir,
             team_state,
+            agent_config: crate::core::config::AgentConfig::default(),
+            tools_config: crate::core::config::ToolsConfig::default(),
+            tool_limiter: Arc::new(ToolLimiter::new(
+                &crate::core::config::ToolsConfig::default().concurrency,
+            )),
+            fast_provider: None,
+            last_run_messages: Arc::new(std::sync::Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_fast_provider(mut self, provider: Option<Arc<dyn Provider>>) -> Self {
+        self.fast_provider = provider;
+        self
+    }
+
+    pub fn with_agent_config(mut self, agent_config: crate::core::config::AgentConfig) -> Self {
+        self.agent_config = agent_config;
+        self
+    }
+
+    pub fn with_tools_config(mut self, tools_config: crate::core::config::ToolsConfig) -> Self {
+        self.tool_limiter = Arc::new(ToolLimiter::new(&tools_config.concurrency));
+        self.tools_config = tools_config;
+        self
+    }
+
+    /// Register caller-provided tools alongside the built-in set, for
+    /// embedders adding domain-specific tools. Fails on the first name
+    /// that collides with an already-registered tool.
+    pub fn with_extra_tools(
+        mut self,
+        extra: Vec<Arc<dyn Tool>>,
+    ) -> Result<Self, ToolError> {
+        for tool in extra {
+            self.register_tool(tool)?;
         }
+        Ok(self)
+    }
+
+    /// Register a single additional tool. Rejects names that collide with
+    /// an existing tool so a plugin can't silently shadow a built-in.
+    pub fn register_tool(&mut self, tool: Arc<dyn Tool>) -> Result<(), ToolError> {
+        let name = tool.definition().name;
+        if self.tools.iter().any(|t| t.definition().name == name) {
+            return Err(ToolError::DuplicateName(name));
+        }
+        self.tools.push(tool);
+        Ok(())
     }
 
     pub fn model_name(&self) -> &str {
@@ -53,6 +134,121 @@ impl Agent {
         self.provider = provider;
     }
 
+    /// Replace the system prompt for subsequent runs, e.g. after context
+    /// files changed on disk or per-session instruction edits. Runs
+    /// already in flight keep the prompt they started with.
+    pub fn set_system_prompt(&mut self, system_prompt: String) {
+        self.system_prompt = system_prompt;
+    }
+
+    pub fn system_prompt(&self) -> &str {
+        &self.system_prompt
+    }
+
+    pub fn set_temperature(&self, temperature: Option<f64>) {
+        self.provider.set_temperature(temperature);
+    }
+
+    pub fn temperature(&self) -> Option<f64> {
+        self.provider.temperature()
+    }
+
+    pub fn set_tool_choice(&self, choice: Option<crate::core::provider::ToolChoice>) {
+        self.provider.set_tool_choice(choice);
+    }
+
+    pub fn tool_choice(&self) -> Option<crate::core::provider::ToolChoice> {
+        self.provider.tool_choice()
+    }
+
+    /// The exact message list the most recent `run` sent to the provider,
+    /// after trimming, compaction, and any tool turns. Useful for callers
+    /// that persist the history themselves, diff it against the DB, or
+    /// test the trimming logic end-to-end. Updated live as the run
+    /// progresses; empty before the first run.
+    pub fn last_run_messages(&self) -> Vec<Message> {
+        self.last_run_messages.lock().unwrap().clone()
+    }
+
+    pub fn set_max_tokens_override(&self, max_tokens: Option<u64>) {
+        self.provider.set_max_tokens_override(max_tokens);
+    }
+
+    pub fn max_tokens_override(&self) -> Option<u64> {
+        self.provider.max_tokens_override()
+    }
+
+    /// Run a single structured-output turn: ask the model to answer with
+    /// JSON matching `schema`, validate the response, and return the
+    /// parsed value.
+    ///
+    /// Sends an OpenAI-style `response_format: {type: "json_schema", ...}`
+    /// when the gateway supports it, falling back to prompt-based JSON
+    /// instructions when it doesn't. No tools are exposed.
+    pub async fn run_structured(
+        &self,
+        session_id: String,
+        mut messages: Vec<Message>,
+        user_input: String,
+        schema: serde_json::Value,
+    ) -> Result<serde_json::Value, OctoError> {
+        messages.push(Message::new_user(session_id, user_input));
+
+        let response_format = serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": "structured_output",
+                "schema": schema,
+                "strict": true,
+            }
+        });
+
+        let response = match self
+            .provider
+            .send_messages_with_format(&messages, &self.system_prompt, &[], Some(&response_format))
+            .await
+        {
+            Ok(r) => r,
+            // Gateways that reject response_format: retry with prompt-based JSON
+            Err(crate::core::error::ProviderError::Api { status: 400, .. }) => {
+                let prompt = format!(
+                    "{}\n\nRespond ONLY with a JSON object matching this JSON schema, \
+                    with no prose or markdown fences:\n{}",
+                    self.system_prompt, response_format["json_schema"]["schema"]
+                );
+                self.provider
+                    .send_messages(&messages, &prompt, &[])
+                    .await
+                    .map_err(OctoError::Provider)?
+            }
+            Err(e) => return Err(OctoError::Provider(e)),
+        };
+
+        let text: String = response
+            .content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let value: serde_json::Value = serde_json::from_str(extract_json(&text))
+            .map_err(|e| {
+                OctoError::Provider(crate::core::error::ProviderError::Stream(format!(
+                    "Structured response is not valid JSON: {e}"
+                )))
+            })?;
+
+        validate_schema(&value, &response_format["json_schema"]["schema"]).map_err(|e| {
+            OctoError::Provider(crate::core::error::ProviderError::Stream(format!(
+                "Structured response does not match schema: {e}"
+            )))
+        })?;
+
+        Ok(value)
+    }
+
     pub fn run(
         &self,
         session_id: String,
@@ -69,6 +265,11 @@ impl Agent {
         let working_dir = self.working_dir.clone();
         let cancel_clone = cancel.clone();
         let team_state = self.team_state.clone();
+        let agent_config = self.agent_config.clone();
+        let tools_config = self.tools_config.clone();
+        let tool_limiter = Arc::clone(&self.tool_limiter);
+        let fast_provider = self.fast_provider.clone();
+        let last_run_messages = Arc::clone(&self.last_run_messages);
 
         tokio::spawn(async move {
             let result = agent_loop(
@@ -83,6 +284,11 @@ impl Agent {
                 tx.clone(),
                 cancel_clone,
                 team_state,
+                agent_config,
+                tools_config,
+                tool_limiter,
+                fast_provider,
+                last_run_messages,
             )
             .await;
 
@@ -113,6 +319,125 @@ fn truncate_tool_result(content: &str, max_chars: usize) -> String {
     )
 }
 
+/// Reduce an oversized tool result to fit the token budget. When enabled
+/// and a fast model is available, summarize the full content so details
+/// from the tail (e.g. the failure at the end of long test output)
+/// survive; otherwise fall back to head-truncation.
+async fn condense_tool_result(
+    content: &str,
+    max_chars: usize,
+    session_id: &str,
+    tool_name: &str,
+    summarize: bool,
+    fast_provider: Option<&Arc<dyn Provider>>,
+) -> String {
+    if content.len() <= max_chars {
+        return content.to_string();
+    }
+
+    if summarize {
+        if let Some(provider) = fast_provider {
+            // Cap what the fast model sees, keeping both ends of the output
+            let excerpt = if content.len() > 100_000 {
+                let head = content.floor_char_boundary(50_000);
+                let tail = content.ceil_char_boundary(content.len() - 50_000);
+                format!("{}\n\n... [middle elided] ...\n\n{}", &content[..head], &content[tail..])
+            } else {
+                content.to_string()
+            };
+            let request = [Message::new_user(
+                session_id.to_string(),
+                format!(
+                    "Summarize this output from the `{tool_name}` tool for a coding agent. \
+                    Preserve error messages, file paths, symbol names, and counts; quote \
+                    the most important lines verbatim.\n\n{excerpt}"
+                ),
+            )];
+            if let Ok(response) = provider
+                .send_messages(&request, "You condense tool output for an AI coding agent.", &[])
+                .await
+            {
+                let summary: String = response
+                    .content
+                    .iter()
+                    .filter_map(|part| match part {
+                        ContentPart::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                if !summary.is_empty() {
+                    return format!(
+                        "[Summarized from {} chars of {tool_name} output]\n{summary}",
+                        content.len()
+                    );
+                }
+            }
+        }
+    }
+
+    truncate_tool_result(content, max_chars)
+}
+
+/// Strip markdown code fences so a ```json block parses as plain JSON
+fn extract_json(text: &str) -> &str {
+    let trimmed = text.trim();
+    if let Some(rest) = trimmed.strip_prefix("```") {
+        let rest = rest.strip_prefix("json").unwrap_or(rest);
+        if let Some(end) = rest.rfind("```") {
+            return rest[..end].trim();
+        }
+    }
+    trimmed
+}
+
+/// Minimal JSON Schema check: verifies `type`, `required` properties, and
+/// nested object properties. Not a full validator, but enough to catch a
+/// model returning the wrong shape.
+fn validate_schema(value: &serde_json::Value, schema: &serde_json::Value) -> Result<(), String> {
+    if let Some(expected) = schema["type"].as_str() {
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => true,
+        };
+        if !matches {
+            return Err(format!("expected type '{expected}', got {value}"));
+        }
+    }
+
+    if let Some(required) = schema["required"].as_array() {
+        for key in required.iter().filter_map(|k| k.as_str()) {
+            if value.get(key).is_none() {
+                return Err(format!("missing required property '{key}'"));
+            }
+        }
+    }
+
+    if let Some(properties) = schema["properties"].as_object() {
+        for (key, prop_schema) in properties {
+            if let Some(prop_value) = value.get(key) {
+                validate_schema(prop_value, prop_schema)
+                    .map_err(|e| format!("property '{key}': {e}"))?;
+            }
+        }
+    }
+
+    if let Some(item_schema) = schema.get("items") {
+        if let Some(items) = value.as_array() {
+            for (i, item) in items.iter().enumerate() {
+                validate_schema(item, item_schema).map_err(|e| format!("item {i}: {e}"))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Estimate token count from text (rough: ~4 chars per token)
 fn estimate_tokens(text: &str) -> u64 {
     (text.len() as u64) / 4
@@ -136,7 +461,12 @@ fn estimate_message_tokens(msg: &Message) -> u64 {
 }
 
 /// Trim messages to fit within context window, keeping system prompt + last user + recent history
-fn trim_messages_to_fit(messages: &mut Vec<Message>, context_window: u64, system_prompt: &str) {
+fn trim_messages_to_fit(
+    messages: &mut Vec<Message>,
+    context_window: u64,
+    system_prompt: &str,
+    keep_recent: usize,
+) {
     let system_tokens = estimate_tokens(system_prompt) + 200; // overhead
     let max_message_tokens = context_window.saturating_sub(system_tokens) * 3 / 4; // 75% for messages, 25% for output
 
@@ -145,8 +475,8 @@ fn trim_messages_to_fit(messages: &mut Vec<Message>, context_window: u64, system
         return;
     }
 
-    // Always keep first user message and last 4 messages
-    let keep_tail = 4.min(messages.len());
+    // Always keep the first user message and the most recent messages
+    let keep_tail = keep_recent.min(messages.len());
     let keep_head = 1.min(messages.len());
 
     if messages.len() <= keep_head + keep_tail {
@@ -176,6 +506,74 @@ fn trim_messages_to_fit(messages: &mut Vec<Message>, context_window: u64, system
     }
 }
 
+/// Replace older messages with an LLM-generated summary, keeping the most
+/// recent `keep_recent` messages verbatim. Returns the number of messages
+/// removed.
+pub(crate) async fn compact_messages(
+    provider: &Arc<dyn Provider>,
+    session_id: &str,
+    messages: &mut Vec<Message>,
+    keep_recent: usize,
+) -> Result<usize, OctoError> {
+    if messages.len() <= keep_recent + 1 {
+        return Ok(0);
+    }
+
+    let split = messages.len() - keep_recent;
+    let mut transcript = String::new();
+    for msg in &messages[..split] {
+        let role = match msg.role {
+            MessageRole::User => "User",
+            MessageRole::Assistant => "Assistant",
+            MessageRole::Tool => "Tool",
+            MessageRole::System => continue,
+        };
+        let text = msg.text_content();
+        if !text.is_empty() {
+            transcript.push_str(&format!("{role}: {}\n", truncate_tool_result(&text, 4_000)));
+        }
+        for (_, name, input) in msg.tool_calls() {
+            transcript.push_str(&format!(
+                "{role} called {name}: {}\n",
+                truncate_tool_result(input, 500)
+            ));
+        }
+    }
+
+    let request = [Message::new_user(
+        session_id.to_string(),
+        format!(
+            "Summarize this conversation so far. Capture the user's goal, key decisions, \
+            files and commands involved, and current state of the work. Be concise but \
+            preserve details needed to continue the task.\n\n{transcript}"
+        ),
+    )];
+    let response = provider
+        .send_messages(&request, "You summarize agent conversations.", &[])
+        .await
+        .map_err(OctoError::Provider)?;
+
+    let summary: String = response
+        .content
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    if summary.is_empty() {
+        return Ok(0);
+    }
+
+    let summary_msg = Message::new_user(
+        session_id.to_string(),
+        format!("[Earlier conversation was compacted. Summary:]\n{summary}"),
+    );
+    messages.splice(..split, [summary_msg]);
+    Ok(split)
+}
+
 async fn agent_loop(
     provider: Arc<dyn Provider>,
     tools: Vec<Arc<dyn Tool>>,
@@ -188,6 +586,11 @@ async fn agent_loop(
     tx: mpsc::Sender<AgentEvent>,
     cancel: CancellationToken,
     team_state: Arc<RwLock<Option<TeamState>>>,
+    agent_config: crate::core::config::AgentConfig,
+    tools_config: crate::core::config::ToolsConfig,
+    tool_limiter: Arc<ToolLimiter>,
+    fast_provider: Option<Arc<dyn Provider>>,
+    last_run_messages: Arc<std::sync::Mutex<Vec<Message>>>,
 ) -> Result<(), OctoError> {
     let tool_defs: Vec<ToolDefinition> = tools.iter().map(|t| t.definition()).collect();
     let context_window = provider.model().context_window;
@@ -195,6 +598,9 @@ async fn agent_loop(
     let user_msg = Message::new_user(session_id.clone(), user_input);
     messages.push(user_msg);
 
+    // Usage accumulated across all provider round-trips in this run
+    let mut cumulative_usage = TokenUsage::default();
+
     let _ = tx
         .send(AgentEvent::Started {
             session_id: session_id.clone(),
@@ -206,28 +612,76 @@ async fn agent_loop(
             return Err(OctoError::Cancelled);
         }
 
+        // Auto-compact before the next turn when the estimated prompt
+        // approaches the context window, replacing old messages with a summary
+        if agent_config.auto_compact {
+            let estimated: u64 = estimate_tokens(&system_prompt)
+                + messages.iter().map(estimate_message_tokens).sum::<u64>();
+            let threshold =
+                (context_window as f64 * agent_config.auto_compact_threshold) as u64;
+            if estimated > threshold {
+                match compact_messages(
+                    &provider,
+                    &session_id,
+                    &mut messages,
+                    agent_config.compact_keep_recent,
+                )
+                .await
+                {
+                    Ok(removed) if removed > 0 => {
+                        let _ = tx
+                            .send(AgentEvent::Compacted {
+                                removed_messages: removed,
+                            })
+                            .await;
+                    }
+                    Ok(_) => {}
+                    // Compaction is best-effort; fall back to lossy trimming below
+                    Err(_) => {}
+                }
+            }
+        }
+
         // Trim messages to fit context window
-        trim_messages_to_fit(&mut messages, context_window, &system_prompt);
+        trim_messages_to_fit(
+            &mut messages,
+            context_window,
+            &system_prompt,
+            agent_config.trim_keep_recent,
+        );
+
+        // Snapshot what's about to go to the provider so callers can
+        // inspect the post-compaction/post-trim history
+        *last_run_messages.lock().unwrap() = messages.clone();
 
         let mut event_stream = 'retry: {
             let mut last_err = None;
-            for agent_attempt in 0..3u32 {
+            for agent_attempt in 0..agent_config.retry_attempts.max(1) {
                 match provider
                     .stream_response(&messages, &system_prompt, &tool_defs)
                     .await
                 {
                     Ok(stream) => break 'retry stream,
                     Err(crate::core::error::ProviderError::RateLimited { retry_after_ms }) => {
-                        let wait = retry_after_ms.max(5_000) * (agent_attempt as u64 + 1);
+                        let wait = (retry_after_ms.max(5_000) as f64
+                            * (1.0 + agent_config.retry_backoff_multiplier * agent_attempt as f64))
+                            as u64;
                         let _ = tx
                             .send(AgentEvent::Error {
                                 error: format!(
-                                    "Rate limited. Waiting {:.0}s... (attempt {}/3)",
+                                    "Rate limited. Waiting {:.0}s... (attempt {}/{})",
                                     wait as f64 / 1000.0,
                                     agent_attempt + 1,
+                                    agent_config.retry_attempts.max(1),
                                 ),
                             })
                             .await;
+                        // Let the UI show pressure and remaining backoff
+                        let _ = tx
+                            .send(AgentEvent::RateLimit {
+                                status: provider.rate_limit_status(),
+                            })
+                            .await;
                         tokio::time::sleep(std::time::Duration::from_millis(wait)).await;
                         last_err = Some(crate::core::error::ProviderError::RateLimited {
                             retry_after_ms: wait,
@@ -240,9 +694,23 @@ async fn agent_loop(
         };
 
         let (assistant_msg, finish_reason, usage) =
-            process_stream(&mut event_stream, &session_id, &provider, &tx, &cancel).await?;
+            process_stream(
+                &mut event_stream,
+                &session_id,
+                &provider,
+                &tx,
+                &cancel,
+                std::time::Duration::from_secs(agent_config.stream_idle_timeout_secs),
+            )
+            .await?;
 
         messages.push(assistant_msg.clone());
+        *last_run_messages.lock().unwrap() = messages.clone();
+
+        cumulative_usage.input_tokens += usage.input_tokens;
+        cumulative_usage.output_tokens += usage.output_tokens;
+        cumulative_usage.cache_creation_tokens += usage.cache_creation_tokens;
+        cumulative_usage.cache_read_tokens += usage.cache_read_tokens;
 
         match finish_reason {
             FinishReason::EndTurn | FinishReason::MaxTokens => {
@@ -250,12 +718,19 @@ async fn agent_loop(
                     .send(AgentEvent::Complete {
                         message: assistant_msg,
                         finish_reason,
-                        usage,
+                        usage: cumulative_usage,
                     })
                     .await;
                 return Ok(());
             }
             FinishReason::ToolUse => {
+                // Let the UI update its token/cost figures mid-loop
+                let _ = tx
+                    .send(AgentEvent::UsageUpdate {
+                        cumulative_usage: cumulative_usage.clone(),
+                    })
+                    .await;
+
                 let tool_calls = assistant_msg.tool_calls();
                 let mut tool_results = Vec::new();
 
@@ -273,6 +748,7 @@ async fn agent_loop(
                         .send(AgentEvent::ToolCallStart {
                             id: call_id.to_string(),
                             name: call_name.to_string(),
+                            input: call_input.to_string(),
                         })
                         .await;
 
@@ -289,7 +765,35 @@ async fn agent_loop(
                         input: call_input.to_string(),
                     };
 
-                    let result = match tool.run(&call, &tool_ctx).await {
+                    // Queue behind the per-tool concurrency limit, if any
+                    let _permit = tool_limiter.acquire(call_name).await;
+
+                    // Bound execution by the configured per-tool timeout so a
+                    // stalled subprocess or server can't hang the loop
+                    let run = async {
+                        match tools_config.timeout_for(call_name) {
+                            Some(timeout) => {
+                                tokio::time::timeout(timeout, tool.run(&call, &tool_ctx))
+                                    .await
+                                    .unwrap_or(Err(ToolError::Timeout(timeout.as_secs())))
+                            }
+                            None => tool.run(&call, &tool_ctx).await,
+                        }
+                    };
+
+                    // Tools are expected to watch the token themselves; the
+                    // select! is a backstop so a non-compliant tool still
+                    // returns promptly on Ctrl+C
+                    let run_result = tokio::select! {
+                        res = run => res,
+                        _ = cancel.cancelled() => Err(ToolError::Cancelled),
+                    };
+
+                    if matches!(run_result, Err(ToolError::Cancelled)) {
+                        return Err(OctoError::Cancelled);
+                    }
+
+                    let result = match run_result {
                         Ok(r) => r,
                         Err(e) => {
                             let err_msg = e.to_string();
@@ -299,6 +803,8 @@ async fn agent_loop(
                                     tool_name: call_name.to_string(),
                                     result: err_msg.clone(),
                                     is_error: true,
+                                    metadata: None,
+                                    affected_paths: Vec::new(),
                                 })
                                 .await;
                             crate::core::tool::ToolResult::error(err_msg)
@@ -312,12 +818,33 @@ async fn agent_loop(
                                 tool_name: call_name.to_string(),
                                 result: result.content.clone(),
                                 is_error: false,
+                                metadata: result.metadata.clone(),
+                                affected_paths: result.affected_paths.clone(),
                             })
                             .await;
                     }
 
-                    // Truncate large tool results to avoid blowing up token usage
-                    let truncated_content = truncate_tool_result(&result.content, 30_000);
+                    // Let index-holding tools (CodeRLM) refresh after a
+                    // successful file mutation, using the paths the tool
+                    // itself reported
+                    if !result.is_error && !result.affected_paths.is_empty() {
+                        for t in tools.iter() {
+                            if t.definition().name != call_name {
+                                t.on_files_changed(&result.affected_paths, &tool_ctx).await;
+                            }
+                        }
+                    }
+
+                    // Reduce large tool results to avoid blowing up token usage
+                    let truncated_content = condense_tool_result(
+                        &result.content,
+                        30_000,
+                        &session_id,
+                        call_name,
+                        agent_config.summarize_tool_results,
+                        fast_provider.as_ref(),
+                    )
+                    .await;
 
                     // Wrap tool result with markers for prompt injection defense
                     let wrapped_content = format!(
@@ -334,13 +861,14 @@ async fn agent_loop(
 
                 let tool_msg = Message::new_tool_result(session_id.clone(), tool_results);
                 messages.push(tool_msg);
+                *last_run_messages.lock().unwrap() = messages.clone();
             }
             _ => {
                 let _ = tx
                     .send(AgentEvent::Complete {
                         message: assistant_msg,
                         finish_reason,
-                        usage,
+                        usage: cumulative_usage,
                     })
                     .await;
                 return Ok(());
@@ -349,12 +877,13 @@ async fn agent_loop(
     }
 }
 
-async fn process_stream(
+pub(crate) async fn process_stream(
     stream: &mut ProviderEventStream,
     session_id: &str,
     provider: &Arc<dyn Provider>,
     tx: &mpsc::Sender<AgentEvent>,
     cancel: &CancellationToken,
+    idle_timeout: std::time::Duration,
 ) -> Result<(Message, FinishReason, TokenUsage), OctoError> {
     let model_id = provider.model().id.clone();
     let mut msg = Message::new_assistant(session_id.to_string(), model_id);
@@ -372,7 +901,18 @@ async fn process_stream(
             _ = cancel.cancelled() => {
                 return Err(OctoError::Cancelled);
             }
-            event = stream.next() => {
+            // A server that hangs mid-stream with TCP open never resolves
+            // `stream.next()`, so bound each poll by the idle timeout
+            event = tokio::time::timeout(idle_timeout, stream.next()) => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(_) => {
+                        return Err(OctoError::Provider(crate::core::error::ProviderError::Stream(format!(
+                            "stream stalled: no data received for {}s",
+                            idle_timeout.as_secs()
+                        ))));
+                    }
+                };
                 match event {
                     None => break,
                     Some(ProviderEvent::ContentDelta { text }) => {
@@ -394,6 +934,13 @@ async fn process_stream(
                     }
                     Some(ProviderEvent::ThinkingDelta { text }) => {
                         let _ = tx.send(AgentEvent::ThinkingDelta { text: text.clone() }).await;
+                        // Flush pending text so a text → thinking → text
+                        // sequence keeps its segments in arrival order
+                        if !current_text.is_empty() {
+                            msg.parts.push(ContentPart::Text {
+                                text: std::mem::take(&mut current_text),
+                            });
+                        }
                         // Buffer thinking instead of pushing each chunk
                         current_thinking.push_str(&text);
                     }
@@ -452,7 +999,8 @@ async fn process_stream(
         }
     }
 
-    // Flush remaining buffers
+    // Flush the remaining buffer. Text and thinking flush each other on
+    // transition, so at most one of them is non-empty here.
     if !current_thinking.is_empty() {
         msg.parts.push(ContentPart::Reasoning {
             text: current_thinking,
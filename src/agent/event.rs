@@ -1,4 +1,5 @@
 use crate::core::message::{FinishReason, Message, TokenUsage};
+use crate::core::provider::RateLimitStatus;
 
 #[derive(Debug, Clone)]
 pub enum AgentEvent {
@@ -14,6 +15,7 @@ pub enum AgentEvent {
     ToolCallStart {
         id: String,
         name: String,
+        input: String,
     },
     ToolCallInputDelta {
         id: String,
@@ -24,6 +26,20 @@ pub enum AgentEvent {
         tool_name: String,
         result: String,
         is_error: bool,
+        /// Structured extras from the tool (e.g. the unified diff a file
+        /// edit produced), passed through for richer UI rendering
+        metadata: Option<serde_json::Value>,
+        /// Files the tool reported as created/modified/deleted
+        affected_paths: Vec<String>,
+    },
+    Compacted {
+        removed_messages: usize,
+    },
+    UsageUpdate {
+        cumulative_usage: TokenUsage,
+    },
+    RateLimit {
+        status: RateLimitStatus,
     },
     Complete {
         message: Message,
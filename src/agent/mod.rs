@@ -4,3 +4,6 @@ pub mod prompt;
 
 pub use agent::Agent;
 pub use event::AgentEvent;
+
+#[cfg(test)]
+mod tests;
@@ -1,6 +1,17 @@
 use std::path::Path;
 
-pub fn build_system_prompt(working_dir: &Path, context_paths: &[String]) -> String {
+/// Per-file cap for always-included files; larger files are skipped with
+/// a note rather than truncated mid-syntax
+const INCLUDE_FILE_MAX_BYTES: usize = 50_000;
+
+/// Total budget for all always-included files combined
+const INCLUDE_TOTAL_MAX_BYTES: usize = 150_000;
+
+pub fn build_system_prompt(
+    working_dir: &Path,
+    context_paths: &[String],
+    include_files: &[String],
+) -> String {
     let mut prompt = String::from(CODER_SYSTEM_PROMPT);
 
     // Add environment info
@@ -25,9 +36,62 @@ pub fn build_system_prompt(working_dir: &Path, context_paths: &[String]) -> Stri
         }
     }
 
+    // Inject always-included source files as read-only context
+    if !include_files.is_empty() {
+        let mut total = 0usize;
+        let mut section = String::from(
+            "\n\n# Included Files\n\
+            These project files are provided as read-only context so you don't \
+            need to `view` them first. Re-read a file before editing it — the \
+            copy here may be stale.\n",
+        );
+        for path in include_files {
+            let full_path = working_dir.join(path);
+            match std::fs::read_to_string(&full_path) {
+                Ok(content) if content.len() > INCLUDE_FILE_MAX_BYTES => {
+                    section.push_str(&format!(
+                        "\n## {path}\n(skipped: {} bytes exceeds the per-file cap)\n",
+                        content.len()
+                    ));
+                }
+                Ok(content) if total + content.len() > INCLUDE_TOTAL_MAX_BYTES => {
+                    section.push_str(&format!(
+                        "\n## {path}\n(skipped: total included-file budget exhausted)\n"
+                    ));
+                }
+                Ok(content) => {
+                    total += content.len();
+                    section.push_str(&format!("\n## {path}\n```\n{content}\n```\n"));
+                }
+                Err(_) => {
+                    section.push_str(&format!("\n## {path}\n(not found)\n"));
+                }
+            }
+        }
+        prompt.push_str(&section);
+    }
+
     prompt
 }
 
+/// Modification times of the configured context files, captured when the
+/// system prompt is built. Comparing two signatures detects files edited,
+/// created, or removed mid-session (missing files record `None`).
+pub fn context_signature(
+    working_dir: &Path,
+    context_paths: &[String],
+) -> Vec<(String, Option<std::time::SystemTime>)> {
+    context_paths
+        .iter()
+        .map(|ctx_path| {
+            let mtime = std::fs::metadata(working_dir.join(ctx_path))
+                .and_then(|m| m.modified())
+                .ok();
+            (ctx_path.clone(), mtime)
+        })
+        .collect()
+}
+
 const CODER_SYSTEM_PROMPT: &str = r#"You are an AI coding assistant with PARALLEL EXECUTION capabilities. You help users with software engineering tasks by coordinating multiple agents working simultaneously.
 
 ## Guidelines
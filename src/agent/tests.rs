@@ -0,0 +1,457 @@
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::core::error::{OctoError, ProviderError};
+use crate::core::message::{ContentPart, FinishReason, Message, TokenUsage};
+use crate::core::model::{get_model, Model, ModelId};
+use crate::core::provider::{
+    Provider, ProviderEvent, ProviderEventStream, ProviderResponse,
+};
+use crate::core::tool::ToolDefinition;
+
+/// Provider stub for tests that only need `model()`
+struct StubProvider {
+    model: Model,
+}
+
+impl StubProvider {
+    fn new() -> Self {
+        Self {
+            model: get_model(&ModelId("zai-org/glm-5".into())).unwrap(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Provider for StubProvider {
+    async fn send_messages(
+        &self,
+        _messages: &[Message],
+        _system_prompt: &str,
+        _tools: &[ToolDefinition],
+    ) -> Result<ProviderResponse, ProviderError> {
+        Err(ProviderError::Stream("not used in this test".into()))
+    }
+
+    async fn stream_response(
+        &self,
+        _messages: &[Message],
+        _system_prompt: &str,
+        _tools: &[ToolDefinition],
+    ) -> Result<ProviderEventStream, ProviderError> {
+        Err(ProviderError::Stream("not used in this test".into()))
+    }
+
+    fn model(&self) -> &Model {
+        &self.model
+    }
+}
+
+fn event_stream(events: Vec<ProviderEvent>) -> ProviderEventStream {
+    Box::pin(tokio_stream::iter(events))
+}
+
+#[tokio::test]
+async fn test_process_stream_preserves_interleaved_order() {
+    let provider: Arc<dyn Provider> = Arc::new(StubProvider::new());
+    let (tx, _rx) = mpsc::channel(256);
+    let cancel = CancellationToken::new();
+
+    // Text, thinking and tool calls interleaved across the turn
+    let mut stream = event_stream(vec![
+        ProviderEvent::ContentDelta { text: "first".into() },
+        ProviderEvent::ThinkingDelta { text: "hmm".into() },
+        ProviderEvent::ToolUseStart {
+            id: "t1".into(),
+            name: "ls".into(),
+        },
+        ProviderEvent::ToolUseDelta {
+            input_json_chunk: "{}".into(),
+        },
+        ProviderEvent::ToolUseStop,
+        ProviderEvent::ContentDelta { text: "sec".into() },
+        ProviderEvent::ContentDelta { text: "ond".into() },
+        ProviderEvent::ToolUseStart {
+            id: "t2".into(),
+            name: "view".into(),
+        },
+        ProviderEvent::ToolUseDelta {
+            input_json_chunk: "{\"path\":\"a.rs\"}".into(),
+        },
+        ProviderEvent::ToolUseStop,
+        ProviderEvent::ContentDelta { text: "third".into() },
+        ProviderEvent::Complete {
+            finish_reason: FinishReason::ToolUse,
+            usage: TokenUsage::default(),
+        },
+    ]);
+
+    let (msg, finish_reason, _usage) =
+        super::agent::process_stream(
+            &mut stream,
+            "test-session",
+            &provider,
+            &tx,
+            &cancel,
+            std::time::Duration::from_secs(90),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(finish_reason, FinishReason::ToolUse);
+
+    let parts: Vec<String> = msg
+        .parts
+        .iter()
+        .map(|p| match p {
+            ContentPart::Text { text } => format!("text:{text}"),
+            ContentPart::Reasoning { text } => format!("reasoning:{text}"),
+            ContentPart::ToolCall { name, .. } => format!("tool:{name}"),
+            ContentPart::Finish { .. } => "finish".into(),
+            _ => "other".into(),
+        })
+        .collect();
+
+    assert_eq!(
+        parts,
+        vec![
+            "text:first",
+            "reasoning:hmm",
+            "tool:ls",
+            "text:second",
+            "tool:view",
+            "text:third",
+            "finish",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_process_stream_errors_on_idle_timeout() {
+    let provider: Arc<dyn Provider> = Arc::new(StubProvider::new());
+    let (tx, _rx) = mpsc::channel(256);
+    let cancel = CancellationToken::new();
+
+    // A stream backed by a channel whose sender stays open but never sends
+    // models a connection that hangs mid-stream with TCP still alive
+    let (stall_tx, stall_rx) = mpsc::channel::<ProviderEvent>(1);
+    stall_tx
+        .send(ProviderEvent::ContentDelta { text: "par".into() })
+        .await
+        .unwrap();
+    let mut stream: ProviderEventStream =
+        Box::pin(tokio_stream::wrappers::ReceiverStream::new(stall_rx));
+
+    let result = super::agent::process_stream(
+        &mut stream,
+        "test-session",
+        &provider,
+        &tx,
+        &cancel,
+        std::time::Duration::from_millis(100),
+    )
+    .await;
+
+    drop(stall_tx);
+    match result {
+        Err(OctoError::Provider(ProviderError::Stream(msg))) => {
+            assert!(msg.contains("stalled"), "unexpected message: {msg}");
+        }
+        other => panic!("expected stream-stall error, got {other:?}"),
+    }
+}
+
+// ─── Cancellation harness ────────────────────────────
+
+/// Provider that plays back scripted turns, pausing between events so a
+/// test can cancel mid-stream at a deterministic point
+struct MockProvider {
+    model: Model,
+    turns: std::sync::Mutex<std::collections::VecDeque<Vec<ProviderEvent>>>,
+    event_delay: std::time::Duration,
+}
+
+impl MockProvider {
+    fn new(turns: Vec<Vec<ProviderEvent>>, event_delay: std::time::Duration) -> Self {
+        Self {
+            model: get_model(&ModelId("zai-org/glm-5".into())).unwrap(),
+            turns: std::sync::Mutex::new(turns.into()),
+            event_delay,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Provider for MockProvider {
+    async fn send_messages(
+        &self,
+        _messages: &[Message],
+        _system_prompt: &str,
+        _tools: &[ToolDefinition],
+    ) -> Result<ProviderResponse, ProviderError> {
+        Err(ProviderError::Stream("not used in this test".into()))
+    }
+
+    async fn stream_response(
+        &self,
+        _messages: &[Message],
+        _system_prompt: &str,
+        _tools: &[ToolDefinition],
+    ) -> Result<ProviderEventStream, ProviderError> {
+        let events = self
+            .turns
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_default();
+        let delay = self.event_delay;
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            for event in events {
+                tokio::time::sleep(delay).await;
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+
+    fn model(&self) -> &Model {
+        &self.model
+    }
+}
+
+struct AllowPerm;
+
+#[async_trait::async_trait]
+impl crate::core::permission::PermissionService for AllowPerm {
+    async fn request(
+        &self,
+        _req: crate::core::permission::PermissionRequest,
+    ) -> crate::core::permission::PermissionDecision {
+        crate::core::permission::PermissionDecision::Allow
+    }
+    fn auto_approve_session(&self, _session_id: &str) {}
+}
+
+/// Tool that sleeps before answering, so cancellation can land while a
+/// tool call is in flight
+struct SleepTool {
+    duration: std::time::Duration,
+}
+
+#[async_trait::async_trait]
+impl crate::core::tool::Tool for SleepTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "sleep".into(),
+            description: "Sleeps for a fixed duration".into(),
+            parameters: std::collections::BTreeMap::new(),
+            required: vec![],
+        }
+    }
+
+    async fn run(
+        &self,
+        _call: &crate::core::tool::ToolCall,
+        _ctx: &crate::core::tool::ToolContext,
+    ) -> Result<crate::core::tool::ToolResult, crate::core::error::ToolError> {
+        tokio::time::sleep(self.duration).await;
+        Ok(crate::core::tool::ToolResult::success("slept".into()))
+    }
+}
+
+fn mock_agent(provider: Arc<dyn Provider>) -> super::Agent {
+    super::Agent::new(
+        provider,
+        vec![Arc::new(SleepTool {
+            duration: std::time::Duration::from_millis(100),
+        })],
+        Arc::new(AllowPerm),
+        "test prompt".into(),
+        std::path::PathBuf::from("/tmp"),
+        Arc::new(std::sync::RwLock::new(None)),
+    )
+}
+
+fn tool_use_turn() -> Vec<ProviderEvent> {
+    vec![
+        ProviderEvent::ToolUseStart {
+            id: "t1".into(),
+            name: "sleep".into(),
+        },
+        ProviderEvent::ToolUseDelta {
+            input_json_chunk: "{}".into(),
+        },
+        ProviderEvent::ToolUseStop,
+        ProviderEvent::Complete {
+            finish_reason: FinishReason::ToolUse,
+            usage: TokenUsage::default(),
+        },
+    ]
+}
+
+/// Drain all remaining events, returning them once the channel closes
+async fn drain(rx: &mut mpsc::Receiver<crate::agent::AgentEvent>) -> Vec<crate::agent::AgentEvent> {
+    let mut events = vec![];
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+    events
+}
+
+#[tokio::test]
+async fn test_last_run_messages_exposes_final_history() {
+    // One tool round-trip, then a plain text turn
+    let provider: Arc<dyn Provider> = Arc::new(MockProvider::new(
+        vec![
+            tool_use_turn(),
+            vec![
+                ProviderEvent::ContentDelta { text: "done".into() },
+                ProviderEvent::Complete {
+                    finish_reason: FinishReason::EndTurn,
+                    usage: TokenUsage::default(),
+                },
+            ],
+        ],
+        std::time::Duration::from_millis(5),
+    ));
+    let agent = mock_agent(provider);
+
+    assert!(agent.last_run_messages().is_empty());
+
+    let (mut rx, _cancel) = agent.run("s1".into(), vec![], "hi".into());
+    let events = drain(&mut rx).await;
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, crate::agent::AgentEvent::Complete { .. })));
+
+    // user → assistant (tool call) → tool result → assistant (text)
+    let history = agent.last_run_messages();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[0].text_content(), "hi");
+    assert_eq!(history[1].tool_calls().len(), 1);
+    assert!(matches!(
+        history[2].parts[0],
+        ContentPart::ToolResult { .. }
+    ));
+    assert_eq!(history[3].text_content(), "done");
+}
+
+#[tokio::test]
+async fn test_cancel_during_content_stream() {
+    let provider: Arc<dyn Provider> = Arc::new(MockProvider::new(
+        vec![vec![
+            ProviderEvent::ContentDelta { text: "one".into() },
+            ProviderEvent::ContentDelta { text: "two".into() },
+            // Never delivered: the test cancels after the first delta
+            ProviderEvent::ContentDelta { text: "three".into() },
+            ProviderEvent::Complete {
+                finish_reason: FinishReason::EndTurn,
+                usage: TokenUsage::default(),
+            },
+        ]],
+        std::time::Duration::from_millis(50),
+    ));
+    let agent = mock_agent(provider);
+
+    let (mut rx, cancel) = agent.run("s1".into(), vec![], "hi".into());
+
+    // Wait for the first content delta, then cancel mid-stream
+    loop {
+        if let crate::agent::AgentEvent::ContentDelta { .. } =
+            rx.recv().await.expect("stream ended before content")
+        {
+            break;
+        }
+    }
+    cancel.cancel();
+
+    let rest = drain(&mut rx).await;
+    assert!(
+        !rest
+            .iter()
+            .any(|e| matches!(e, crate::agent::AgentEvent::Complete { .. })),
+        "cancelled run must not complete"
+    );
+    match rest.last() {
+        Some(crate::agent::AgentEvent::Error { error }) => {
+            assert_eq!(error, "Cancelled");
+        }
+        other => panic!("expected a final Error event, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_during_tool_call() {
+    let provider: Arc<dyn Provider> = Arc::new(MockProvider::new(
+        vec![tool_use_turn(), tool_use_turn()],
+        std::time::Duration::from_millis(5),
+    ));
+    let agent = mock_agent(provider);
+
+    let (mut rx, cancel) = agent.run("s1".into(), vec![], "hi".into());
+
+    // Cancel while the sleep tool is running
+    loop {
+        if let crate::agent::AgentEvent::ToolCallStart { .. } =
+            rx.recv().await.expect("stream ended before tool call")
+        {
+            break;
+        }
+    }
+    cancel.cancel();
+
+    let rest = drain(&mut rx).await;
+    assert!(
+        !rest
+            .iter()
+            .any(|e| matches!(e, crate::agent::AgentEvent::Complete { .. })),
+        "cancelled run must not complete"
+    );
+    match rest.last() {
+        Some(crate::agent::AgentEvent::Error { error }) => {
+            assert_eq!(error, "Cancelled");
+        }
+        other => panic!("expected a final Error event, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_between_turns() {
+    // Second turn's events arrive slowly, leaving a window between the
+    // first tool round-trip and the next model response
+    let provider: Arc<dyn Provider> = Arc::new(MockProvider::new(
+        vec![tool_use_turn(), tool_use_turn()],
+        std::time::Duration::from_millis(5),
+    ));
+    let agent = mock_agent(provider);
+
+    let (mut rx, cancel) = agent.run("s1".into(), vec![], "hi".into());
+
+    // Wait for the first tool result, then cancel before the next turn
+    loop {
+        if let crate::agent::AgentEvent::ToolResult { .. } =
+            rx.recv().await.expect("stream ended before tool result")
+        {
+            break;
+        }
+    }
+    cancel.cancel();
+
+    let rest = drain(&mut rx).await;
+    assert!(
+        !rest
+            .iter()
+            .any(|e| matches!(e, crate::agent::AgentEvent::Complete { .. })),
+        "cancelled run must not complete"
+    );
+    match rest.last() {
+        Some(crate::agent::AgentEvent::Error { error }) => {
+            assert_eq!(error, "Cancelled");
+        }
+        other => panic!("expected a final Error event, got {other:?}"),
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+
+use crate::core::config::AppConfig;
+
+/// Run environment diagnostics: config, API key, CodeRLM, database, and
+/// data directories. All checks are read-only dry runs.
+pub async fn run(config: &AppConfig) -> Result<()> {
+    println!("octo-code doctor\n");
+
+    let mut failures = 0;
+
+    // Config
+    if config.working_dir.is_dir() {
+        check_ok(
+            "Config",
+            &format!(
+                "working dir {} · provider {:?} · model {}",
+                config.working_dir.display(),
+                config.provider_type,
+                config.agent.coder_model.0
+            ),
+        );
+    } else {
+        failures += 1;
+        check_fail(
+            "Config",
+            &format!("working dir {} does not exist", config.working_dir.display()),
+        );
+    }
+
+    // API key + provider connectivity
+    if config.get_active_api_keys().is_empty() {
+        failures += 1;
+        check_fail(
+            "API key",
+            "no key configured (set ATLAS_API_KEY or OPENROUTER_API_KEY)",
+        );
+    } else {
+        let ping = crate::providers::check_connectivity(config).await;
+        if ping.is_healthy() {
+            check_ok("API key", &format!("provider reachable ({}ms)", ping.latency_ms));
+        } else {
+            failures += 1;
+            let detail = match (&ping.error, ping.auth_ok, ping.model_available) {
+                (Some(e), false, _) => format!("authentication failed: {e}"),
+                (Some(e), _, false) => format!("model unavailable: {e}"),
+                (Some(e), _, _) => e.clone(),
+                (None, _, _) => "unknown failure".into(),
+            };
+            check_fail("API key", &detail);
+        }
+    }
+
+    // CodeRLM
+    if crate::tools::is_coderlm_available(&config.coderlm.server_url, &config.http).await {
+        check_ok("CodeRLM", &format!("reachable at {}", config.coderlm.server_url));
+    } else {
+        // Optional: not reachable is informational, not a failure
+        check_warn(
+            "CodeRLM",
+            &format!("not reachable at {} (semantic tools disabled)", config.coderlm.server_url),
+        );
+    }
+
+    // Database
+    match crate::storage::Database::open(config).await {
+        Ok(db) => match db.run_migrations().await {
+            Ok(()) => check_ok("Database", &format!("opened {}", config.data_path().display())),
+            Err(e) => {
+                failures += 1;
+                check_fail("Database", &format!("migrations failed: {e}"));
+            }
+        },
+        Err(e) => {
+            failures += 1;
+            check_fail("Database", &format!("cannot open: {e}"));
+        }
+    }
+
+    // Writable data and team directories
+    for (label, dir) in [
+        ("Data dir", config.data_path()),
+        ("Team dir", crate::core::team::default_base_dir()),
+    ] {
+        if dir_writable(&dir) {
+            check_ok(label, &format!("writable {}", dir.display()));
+        } else {
+            failures += 1;
+            check_fail(label, &format!("not writable: {}", dir.display()));
+        }
+    }
+
+    println!();
+    if failures == 0 {
+        println!("\x1b[32mAll checks passed.\x1b[0m");
+        Ok(())
+    } else {
+        anyhow::bail!("{failures} check(s) failed")
+    }
+}
+
+fn check_ok(label: &str, detail: &str) {
+    println!("  \x1b[32m✓\x1b[0m {label:<10} {detail}");
+}
+
+fn check_warn(label: &str, detail: &str) {
+    println!("  \x1b[33m-\x1b[0m {label:<10} {detail}");
+}
+
+fn check_fail(label: &str, detail: &str) {
+    println!("  \x1b[31m✗\x1b[0m {label:<10} {detail}");
+}
+
+/// Check that a directory exists (creating it if needed) and is writable
+/// by round-tripping a probe file.
+fn dir_writable(dir: &std::path::Path) -> bool {
+    if std::fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let probe = dir.join(".octo-doctor-probe");
+    let ok = std::fs::write(&probe, b"ok").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    ok
+}
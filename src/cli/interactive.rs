@@ -54,6 +54,7 @@ fn select_provider(current: ProviderType) -> Result<ProviderType> {
     let default_idx = match current {
         ProviderType::AtlasCloud => 0,
         ProviderType::OpenRouter => 1,
+        ProviderType::Anthropic => 2,
     };
 
     eprintln!("\x1b[1;36m  Select API provider:\x1b[0m\n");
@@ -61,6 +62,7 @@ fn select_provider(current: ProviderType) -> Result<ProviderType> {
     let providers = [
         ("Atlas Cloud", "api.atlascloud.ai", "GLM, Kimi, Qwen, DeepSeek"),
         ("OpenRouter", "openrouter.ai", "GLM, Kimi, Qwen, DeepSeek"),
+        ("Anthropic", "api.anthropic.com", "Claude (native Messages API)"),
     ];
 
     for (i, (name, url, models)) in providers.iter().enumerate() {
@@ -103,7 +105,8 @@ fn select_provider(current: ProviderType) -> Result<ProviderType> {
 
     let chosen = match idx {
         0 => ProviderType::AtlasCloud,
-        _ => ProviderType::OpenRouter,
+        1 => ProviderType::OpenRouter,
+        _ => ProviderType::Anthropic,
     };
 
     let (name, url, _) = providers[idx];
@@ -216,6 +219,7 @@ fn prompt_api_key(config: &mut crate::core::config::AppConfig) -> Result<()> {
     let (provider_name, key_env) = match config.provider_type {
         ProviderType::AtlasCloud => ("Atlas Cloud", "ATLAS_API_KEY"),
         ProviderType::OpenRouter => ("OpenRouter", "OPENROUTER_API_KEY"),
+        ProviderType::Anthropic => ("Anthropic", "ANTHROPIC_API_KEY"),
     };
 
     let active_keys = config.get_active_api_keys();
@@ -265,6 +269,9 @@ fn apply_api_key(config: &mut crate::core::config::AppConfig, key: &str) {
         ProviderType::OpenRouter => {
             config.openrouter_api_key = Some(key.to_string());
         }
+        ProviderType::Anthropic => {
+            config.anthropic_api_key = Some(key.to_string());
+        }
         ProviderType::AtlasCloud => {
             config.api_key = Some(key.to_string());
             config.api_keys = vec![key.to_string()];
@@ -303,6 +310,12 @@ fn save_api_key_to_config(config: &crate::core::config::AppConfig) -> Result<()>
                     file_config["provider_type"] = serde_json::Value::String("open_router".into());
                 }
             }
+            ProviderType::Anthropic => {
+                if let Some(ref key) = config.anthropic_api_key {
+                    file_config["anthropic_api_key"] = serde_json::Value::String(key.clone());
+                    file_config["provider_type"] = serde_json::Value::String("anthropic".into());
+                }
+            }
             ProviderType::AtlasCloud => {
                 if let Some(ref key) = config.api_key {
                     file_config["api_key"] = serde_json::Value::String(key.clone());
@@ -413,6 +426,7 @@ pub async fn run(
     let tools = crate::tools::create_all_tools(
         permission_service.clone(),
         config.coderlm.server_url.clone(),
+        &config.http,
         team_state.clone(),
     )
     .await;
@@ -420,6 +434,7 @@ pub async fn run(
     let system_prompt = crate::agent::prompt::build_system_prompt(
         &config.working_dir,
         &config.context_paths,
+        &config.include_files,
     );
 
     let agent = crate::agent::Agent::new(
@@ -429,6 +444,11 @@ pub async fn run(
         system_prompt,
         config.working_dir.clone(),
         team_state,
+    )
+    .with_agent_config(config.agent.clone())
+    .with_tools_config(config.tools.clone())
+    .with_fast_provider(
+        crate::providers::create_provider_for_role(&config, crate::providers::ModelRole::Fast).ok(),
     );
 
     // Session
@@ -525,6 +545,7 @@ pub async fn run(
                     let (provider_name, key_env) = match config.provider_type {
                         ProviderType::AtlasCloud => ("Atlas Cloud", "ATLAS_API_KEY"),
                         ProviderType::OpenRouter => ("OpenRouter", "OPENROUTER_API_KEY"),
+                        ProviderType::Anthropic => ("Anthropic", "ANTHROPIC_API_KEY"),
                     };
                     let active_keys = config.get_active_api_keys();
                     if !active_keys.is_empty() {
@@ -1,3 +1,4 @@
+pub mod doctor;
 pub mod interactive;
 pub mod noninteractive;
 pub mod output;
@@ -15,6 +16,9 @@ use tracing_subscriber::EnvFilter;
 #[derive(Parser, Debug)]
 #[command(name = "octo-code", version, about = "AI coding assistant for the terminal")]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Non-interactive mode: provide a prompt directly
     #[arg(short, long)]
     prompt: Option<String>,
@@ -31,6 +35,10 @@ struct Cli {
     #[arg(short, long)]
     quiet: bool,
 
+    /// Write a JSON run summary to this path (non-interactive mode)
+    #[arg(long)]
+    summary_file: Option<PathBuf>,
+
     /// Use simple REPL mode instead of default interactive
     #[arg(long)]
     repl: bool,
@@ -64,6 +72,12 @@ struct Cli {
     agent_name: Option<String>,
 }
 
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Diagnose the environment: config, API key, CodeRLM, database, data dirs
+    Doctor,
+}
+
 #[derive(Debug, Clone, clap::ValueEnum)]
 pub enum OutputFormat {
     Text,
@@ -90,7 +104,28 @@ pub async fn run_cli() -> Result<()> {
         .with_target(false)
         .init();
 
-    let mut config = crate::core::config::load_config(cli.working_dir.clone())
+    // Validate and canonicalize the working directory up front so tools
+    // and the data dir operate on a stable absolute path
+    let working_dir = match &cli.working_dir {
+        Some(dir) => {
+            if !dir.exists() {
+                anyhow::bail!(
+                    "Working directory '{}' does not exist. Create it first or pass an existing path to --cwd.",
+                    dir.display()
+                );
+            }
+            if !dir.is_dir() {
+                anyhow::bail!("'{}' is not a directory.", dir.display());
+            }
+            let canonical = dir.canonicalize().map_err(|e| {
+                anyhow::anyhow!("Cannot resolve working directory '{}': {e}", dir.display())
+            })?;
+            Some(canonical)
+        }
+        None => None,
+    };
+
+    let mut config = crate::core::config::load_config(working_dir)
         .map_err(|e| anyhow::anyhow!("{e}"))?;
 
     // Apply --provider flag if set
@@ -102,12 +137,22 @@ pub async fn run_cli() -> Result<()> {
             "openrouter" | "open_router" => {
                 config.provider_type = crate::core::config::ProviderType::OpenRouter;
             }
+            "anthropic" => {
+                config.provider_type = crate::core::config::ProviderType::Anthropic;
+            }
             _ => {
-                anyhow::bail!("Unknown provider '{}'. Use 'atlas' or 'openrouter'.", provider_str);
+                anyhow::bail!(
+                    "Unknown provider '{}'. Use 'atlas', 'openrouter' or 'anthropic'.",
+                    provider_str
+                );
             }
         }
     }
 
+    if let Some(Command::Doctor) = cli.command {
+        return doctor::run(&config).await;
+    }
+
     // For non-interactive modes, check API key upfront
     // Interactive mode handles key input in its own flow
     if cli.prompt.is_some() || cli.repl || cli.tui {
@@ -158,7 +203,7 @@ pub async fn run_cli() -> Result<()> {
         // Non-interactive: use CLI permission service
         let app =
             build_app_with_cli_permissions(config, db, model_id.as_ref(), team_state.clone()).await?;
-        noninteractive::run(app, prompt, cli.output_format, cli.quiet).await
+        noninteractive::run(app, prompt, cli.output_format, cli.quiet, cli.summary_file).await
     } else if cli.repl {
         // REPL: use CLI permission service
         let app =
@@ -203,6 +248,7 @@ async fn build_app(
     let tools = crate::tools::create_all_tools(
         permission_service.clone(),
         config.coderlm.server_url.clone(),
+        &config.http,
         team_state.clone(),
     )
     .await;
@@ -210,6 +256,7 @@ async fn build_app(
     let system_prompt = crate::agent::prompt::build_system_prompt(
         &config.working_dir,
         &config.context_paths,
+        &config.include_files,
     );
 
     let agent = crate::agent::Agent::new(
@@ -219,6 +266,11 @@ async fn build_app(
         system_prompt,
         config.working_dir.clone(),
         team_state,
+    )
+    .with_agent_config(config.agent.clone())
+    .with_tools_config(config.tools.clone())
+    .with_fast_provider(
+        crate::providers::create_provider_for_role(&config, crate::providers::ModelRole::Fast).ok(),
     );
 
     Ok(App {
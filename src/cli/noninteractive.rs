@@ -1,11 +1,88 @@
 use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
 use crate::agent::AgentEvent;
+use crate::core::message::{FinishReason, TokenUsage};
+
+/// Machine-readable summary of a non-interactive run, for CI pipelines
+/// that need to decide pass/fail or post results
+#[derive(Debug, Default, Serialize)]
+struct RunSummary {
+    files_changed: Vec<String>,
+    commands_run: Vec<String>,
+    finish_reason: Option<FinishReason>,
+    usage: TokenUsage,
+    cost: f64,
+    tool_errors: u64,
+    error: Option<String>,
+}
+
+impl RunSummary {
+    fn record(&mut self, event: &AgentEvent) {
+        match event {
+            AgentEvent::ToolCallStart { name, input, .. } => {
+                if name == "bash" {
+                    let params: serde_json::Value =
+                        serde_json::from_str(input).unwrap_or(serde_json::json!({}));
+                    if let Some(cmd) = params["command"].as_str() {
+                        self.commands_run.push(cmd.to_string());
+                    }
+                }
+            }
+            AgentEvent::ToolResult {
+                tool_name,
+                result,
+                is_error,
+                affected_paths,
+                ..
+            } => {
+                if *is_error {
+                    self.tool_errors += 1;
+                } else {
+                    // Tool-reported paths first; fall back to scraping the
+                    // result text for tools that don't set them
+                    let paths = if affected_paths.is_empty() {
+                        super::tui::extract_file_path(tool_name, result)
+                            .into_iter()
+                            .collect()
+                    } else {
+                        affected_paths.clone()
+                    };
+                    for path in paths {
+                        if !self.files_changed.contains(&path) {
+                            self.files_changed.push(path);
+                        }
+                    }
+                }
+            }
+            AgentEvent::Complete {
+                finish_reason,
+                usage,
+                ..
+            } => {
+                self.finish_reason = Some(*finish_reason);
+                self.usage = usage.clone();
+            }
+            AgentEvent::Error { error } => {
+                self.error = Some(error.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn write_to(&self, path: &PathBuf) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
 
 pub async fn run(
     app: super::App,
     prompt: String,
     output_format: super::OutputFormat,
     quiet: bool,
+    summary_file: Option<PathBuf>,
 ) -> Result<()> {
     // Create session
     let truncated: String = prompt.chars().take(80).collect();
@@ -26,15 +103,30 @@ pub async fn run(
 
     let (mut rx, _cancel) = app.agent.run(session.id.clone(), vec![], prompt);
 
+    // Relay events through a collector so the summary sees everything the
+    // renderer does
+    let (fwd_tx, mut fwd_rx) = tokio::sync::mpsc::channel(256);
+    let collector = tokio::spawn(async move {
+        let mut summary = RunSummary::default();
+        while let Some(event) = rx.recv().await {
+            summary.record(&event);
+            if fwd_tx.send(event).await.is_err() {
+                break;
+            }
+        }
+        summary
+    });
+
     match output_format {
         super::OutputFormat::Text => {
-            super::output::render_stream(&mut rx, quiet, None).await?;
+            super::output::render_stream(&mut fwd_rx, quiet, None).await?;
         }
         super::OutputFormat::Json => {
             let mut full_content = String::new();
             let mut total_usage = crate::core::message::TokenUsage::default();
+            let mut run_error = None;
 
-            while let Some(event) = rx.recv().await {
+            while let Some(event) = fwd_rx.recv().await {
                 match event {
                     AgentEvent::ContentDelta { text } => {
                         full_content.push_str(&text);
@@ -43,26 +135,38 @@ pub async fn run(
                         total_usage = usage;
                     }
                     AgentEvent::Error { error } => {
-                        let output = serde_json::json!({
-                            "error": error,
-                        });
-                        println!("{}", serde_json::to_string_pretty(&output)?);
-                        return Ok(());
+                        run_error = Some(error);
+                        break;
                     }
                     _ => {}
                 }
             }
 
-            let output = serde_json::json!({
-                "content": full_content,
-                "usage": {
-                    "input_tokens": total_usage.input_tokens,
-                    "output_tokens": total_usage.output_tokens,
-                },
-            });
+            let output = match run_error {
+                Some(error) => serde_json::json!({ "error": error }),
+                None => serde_json::json!({
+                    "content": full_content,
+                    "usage": {
+                        "input_tokens": total_usage.input_tokens,
+                        "output_tokens": total_usage.output_tokens,
+                    },
+                }),
+            };
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
     }
 
+    let mut summary = collector.await?;
+    if let Some(path) = summary_file {
+        if let Some(m) = crate::core::model::get_model(app.agent.model_id()) {
+            summary.cost = m.calculate_cost(
+                summary.usage.input_tokens,
+                summary.usage.cache_read_tokens,
+                summary.usage.output_tokens,
+            );
+        }
+        summary.write_to(&path)?;
+    }
+
     Ok(())
 }
@@ -24,6 +24,8 @@ pub async fn run(app: super::App, resume_session: Option<String>) -> Result<()>
         }
     };
 
+    let mut last_input: Option<String> = None;
+
     loop {
         eprint!("\x1b[32;1mocto>\x1b[0m ");
         io::stderr().flush().ok();
@@ -42,11 +44,23 @@ pub async fn run(app: super::App, resume_session: Option<String>) -> Result<()>
             }
         }
 
-        let input = input.trim().to_string();
+        let mut input = input.trim().to_string();
         if input.is_empty() {
             continue;
         }
 
+        // /retry re-submits the last prompt, so handle it here where the
+        // stored input is in scope
+        if input == "/retry" {
+            match &last_input {
+                Some(prev) => input = prev.clone(),
+                None => {
+                    eprintln!("Nothing to retry.");
+                    continue;
+                }
+            }
+        }
+
         // Handle slash commands
         if input.starts_with('/') {
             match handle_command(&input, &app, &session).await {
@@ -68,6 +82,7 @@ pub async fn run(app: super::App, resume_session: Option<String>) -> Result<()>
             .unwrap_or_default();
 
         // Run the agent
+        last_input = Some(input.clone());
         let (mut rx, _cancel) = app.agent.run(session.id.clone(), messages, input);
 
         // Render streaming output
@@ -89,6 +104,8 @@ async fn handle_command(
             println!("  /sessions   List sessions");
             println!("  /clear      Clear current session messages");
             println!("  /model      Show current model");
+            println!("  /set        Set temperature/max_tokens for next turns");
+            println!("  /retry      Re-submit the last prompt");
             println!("  /cost       Show token usage & cost");
             println!("  /exit       Exit");
             Ok(true)
@@ -138,6 +155,35 @@ async fn handle_command(
             );
             Ok(true)
         }
+        s if s == "/set" || s.starts_with("/set ") => {
+            let mut parts = s.split_whitespace().skip(1);
+            match (parts.next(), parts.next()) {
+                (Some("temperature" | "temp"), Some("default")) => {
+                    app.agent.set_temperature(None);
+                    println!("Temperature reset to default.");
+                }
+                (Some("temperature" | "temp"), Some(v)) => match v.parse::<f64>() {
+                    Ok(t) if (0.0..=2.0).contains(&t) => {
+                        app.agent.set_temperature(Some(t));
+                        println!("Temperature set to {t}.");
+                    }
+                    _ => eprintln!("Invalid temperature '{v}' (expected 0.0-2.0 or 'default')."),
+                },
+                (Some("max_tokens"), Some("default")) => {
+                    app.agent.set_max_tokens_override(None);
+                    println!("max_tokens reset to default.");
+                }
+                (Some("max_tokens"), Some(v)) => match v.parse::<u64>() {
+                    Ok(m) if m > 0 => {
+                        app.agent.set_max_tokens_override(Some(m));
+                        println!("max_tokens set to {m}.");
+                    }
+                    _ => eprintln!("Invalid max_tokens '{v}' (expected a positive integer)."),
+                },
+                _ => eprintln!("Usage: /set <temperature|max_tokens> <value|default>"),
+            }
+            Ok(true)
+        }
         "/clear" => {
             app.db
                 .messages()
@@ -1,9 +1,5 @@
 use anyhow::Result;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     prelude::CrosstermBackend,
@@ -24,10 +20,8 @@ struct SetupState {
 
 /// Show API key setup screen. Returns the entered API key or None if cancelled.
 pub fn run_setup() -> Result<Option<String>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    let _guard = super::tui::TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     let mut state = SetupState {
@@ -38,13 +32,7 @@ pub fn run_setup() -> Result<Option<String>> {
         selected_field: 0,
     };
 
-    let result = setup_loop(&mut terminal, &mut state);
-
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
-    result
+    setup_loop(&mut terminal, &mut state)
 }
 
 fn setup_loop(
@@ -65,6 +53,13 @@ fn setup_loop(
                 (KeyCode::Char('v'), KeyModifiers::CONTROL) => {
                     state.show_key = !state.show_key;
                 }
+                // Clear the field and start over without aborting setup
+                (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                    state.api_key.clear();
+                    state.cursor = 0;
+                    state.error_message = None;
+                    state.selected_field = 0;
+                }
                 // Tab to switch fields
                 (KeyCode::Tab, _) | (KeyCode::BackTab, _) => {
                     state.selected_field = if state.selected_field == 0 { 1 } else { 0 };
@@ -92,6 +87,10 @@ fn setup_loop(
                     state.cursor += c.len_utf8();
                     state.error_message = None;
                 }
+                // Backspace on a later field steps back instead of aborting
+                (KeyCode::Backspace, _) if state.selected_field > 0 => {
+                    state.selected_field -= 1;
+                }
                 (KeyCode::Backspace, _) if state.selected_field == 0 => {
                     if state.cursor > 0 {
                         let prev = state.api_key[..state.cursor]
@@ -252,6 +251,8 @@ fn setup_ui(f: &mut Frame, state: &SetupState) {
         ),
         Span::styled("  │  Tab", Style::default().fg(Color::Cyan)),
         Span::styled(": switch field", Style::default().fg(Color::Rgb(140, 140, 140))),
+        Span::styled("  │  Ctrl+U", Style::default().fg(Color::Cyan)),
+        Span::styled(": clear", Style::default().fg(Color::Rgb(140, 140, 140))),
         Span::styled("  │  Esc", Style::default().fg(Color::Cyan)),
         Span::styled(": cancel", Style::default().fg(Color::Rgb(140, 140, 140))),
     ]));
@@ -248,7 +248,16 @@ impl SessionDialog {
                         style,
                     ),
                     Span::styled(
-                        format!("  {} msgs  ${:.4}", s.message_count, s.cost),
+                        format!(
+                            "  {} msgs  ${:.4}  {}",
+                            s.message_count,
+                            s.cost,
+                            // Model without the vendor prefix, to fit the row
+                            s.model_id
+                                .as_deref()
+                                .map(|m| m.rsplit('/').next().unwrap_or(m))
+                                .unwrap_or("-"),
+                        ),
                         Style::default().fg(DIM),
                     ),
                 ]));
@@ -319,11 +328,41 @@ impl CommandDialog {
                 description: "Show token usage".into(),
                 shortcut: "".into(),
             },
+            CommandEntry {
+                name: "/retry".into(),
+                description: "Re-submit the last message".into(),
+                shortcut: "Ctrl+R".into(),
+            },
+            CommandEntry {
+                name: "/edit".into(),
+                description: "Edit the last message and regenerate".into(),
+                shortcut: "Ctrl+G".into(),
+            },
+            CommandEntry {
+                name: "/system".into(),
+                description: "Inspect or modify the system prompt".into(),
+                shortcut: "".into(),
+            },
+            CommandEntry {
+                name: "/reload-context".into(),
+                description: "Reload context files into the system prompt".into(),
+                shortcut: "".into(),
+            },
+            CommandEntry {
+                name: "/notools".into(),
+                description: "Answer one prompt without calling tools".into(),
+                shortcut: "".into(),
+            },
             CommandEntry {
                 name: "/sidebar".into(),
                 description: "Toggle file sidebar".into(),
                 shortcut: "Ctrl+B".into(),
             },
+            CommandEntry {
+                name: "/diff".into(),
+                description: "View diffs of changed files".into(),
+                shortcut: "Ctrl+F".into(),
+            },
             CommandEntry {
                 name: "/exit".into(),
                 description: "Exit OctoCode".into(),
@@ -487,7 +526,11 @@ impl HelpDialog {
             help_line("Ctrl+S", "Switch session"),
             help_line("Ctrl+K", "Command palette"),
             help_line("Ctrl+L", "Compact conversation"),
+            help_line("Ctrl+R", "Retry last message"),
+            help_line("Ctrl+G", "Edit last message"),
             help_line("Ctrl+B", "Toggle sidebar"),
+            help_line("Ctrl+F", "View diffs of changed files"),
+            help_line("Ctrl+Left/Right", "Resize sidebar"),
             help_line("Up/Down", "Scroll chat"),
             help_line("PgUp/PgDn", "Scroll page"),
             help_line("F1", "Show this help"),
@@ -524,6 +567,125 @@ fn help_line<'a>(key: &'a str, desc: &'a str) -> Line<'a> {
     ])
 }
 
+// ─── Diff Viewer ─────────────────────────────────────
+
+/// Unified diffs of the session's changed files, one file at a time.
+/// Left/Right (or Tab) cycles files, Up/Down scrolls the hunks.
+pub struct DiffDialog {
+    pub files: Vec<(String, String)>, // path, accumulated diff
+    pub selected: usize,
+    pub scroll: u16,
+}
+
+pub enum DiffDialogAction {
+    None,
+    Close,
+}
+
+impl DiffDialog {
+    pub fn new(files: Vec<(String, String)>, selected: usize) -> Self {
+        let selected = selected.min(files.len().saturating_sub(1));
+        Self {
+            files,
+            selected,
+            scroll: 0,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> DiffDialogAction {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => DiffDialogAction::Close,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.selected = self.selected.saturating_sub(1);
+                self.scroll = 0;
+                DiffDialogAction::None
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => {
+                if self.selected + 1 < self.files.len() {
+                    self.selected += 1;
+                } else {
+                    self.selected = 0;
+                }
+                self.scroll = 0;
+                DiffDialogAction::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                DiffDialogAction::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+                DiffDialogAction::None
+            }
+            KeyCode::PageUp => {
+                self.scroll = self.scroll.saturating_sub(10);
+                DiffDialogAction::None
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_add(10);
+                DiffDialogAction::None
+            }
+            _ => DiffDialogAction::None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        render_dimmed_bg(f, area);
+
+        let width = area.width.saturating_sub(6).max(40);
+        let height = area.height.saturating_sub(4).max(10);
+        let dialog_area = centered_rect(width, height, area);
+
+        let title = match self.files.get(self.selected) {
+            Some((path, _)) => format!(
+                "Diff \u{2014} {} ({}/{})",
+                path,
+                self.selected + 1,
+                self.files.len()
+            ),
+            None => "Diff".to_string(),
+        };
+        let block = dialog_block(&title);
+        let inner = block.inner(dialog_area);
+        f.render_widget(block, dialog_area);
+
+        let mut lines = Vec::new();
+        match self.files.get(self.selected) {
+            Some((_, diff)) if !diff.is_empty() => {
+                for line in diff.lines() {
+                    let style = if line.starts_with("@@") {
+                        Style::default().fg(Color::Cyan)
+                    } else if line.starts_with('+') {
+                        Style::default().fg(Color::Green)
+                    } else if line.starts_with('-') {
+                        Style::default().fg(Color::Red)
+                    } else {
+                        Style::default().fg(DIM)
+                    };
+                    lines.push(Line::from(Span::styled(format!(" {line}"), style)));
+                }
+            }
+            _ => {
+                lines.push(Line::from(Span::styled(
+                    "  No diff recorded for this file.",
+                    Style::default().fg(DIM),
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "  Left/Right: file  Up/Down: scroll  Esc: close",
+            Style::default().fg(DIM),
+        )));
+
+        // Clamp scrolling to the content so the hint stays reachable
+        let max_scroll = (lines.len() as u16).saturating_sub(inner.height);
+        self.scroll = self.scroll.min(max_scroll);
+
+        f.render_widget(Paragraph::new(lines).scroll((self.scroll, 0)), inner);
+    }
+}
+
 // ─── Permission Dialog ───────────────────────────────
 
 pub struct PermissionDialog {
@@ -15,8 +15,10 @@ const TEXT_FG: Color = Color::Rgb(210, 210, 225);
 const INLINE_CODE_FG: Color = Color::Rgb(220, 180, 120);
 const INLINE_CODE_BG: Color = Color::Rgb(35, 35, 55);
 
-/// Parse a markdown string into ratatui Lines with syntax highlighting
-pub fn render_markdown(text: &str, indent: &str) -> Vec<Line<'static>> {
+/// Parse a markdown string into ratatui Lines with syntax highlighting.
+/// `width` is the render area width; code-block lines are wrapped to it
+/// (prose lines wrap in the Paragraph widget).
+pub fn render_markdown(text: &str, indent: &str, width: u16) -> Vec<Line<'static>> {
     let mut lines: Vec<Line<'static>> = Vec::new();
     let mut in_code_block = false;
     let mut code_lang = String::new();
@@ -49,10 +51,7 @@ pub fn render_markdown(text: &str, indent: &str) -> Vec<Line<'static>> {
         }
 
         if in_code_block {
-            lines.push(Line::from(Span::styled(
-                format!("{indent}  \u{2502} {raw_line}"),
-                Style::default().fg(CODE_FG).bg(CODE_BG),
-            )));
+            push_code_line(&mut lines, raw_line, indent, width);
             continue;
         }
 
@@ -148,6 +147,46 @@ pub fn render_markdown(text: &str, indent: &str) -> Vec<Line<'static>> {
     lines
 }
 
+/// Push a code-block line, wrapping it to the available width so it never
+/// overflows horizontally. Continuation lines repeat the code's leading
+/// indentation and are marked with `\u{21AA}`.
+fn push_code_line(lines: &mut Vec<Line<'static>>, raw_line: &str, indent: &str, width: u16) {
+    let style = Style::default().fg(CODE_FG).bg(CODE_BG);
+    let prefix = format!("{indent}  \u{2502} ");
+    let avail = (width as usize)
+        .saturating_sub(prefix.chars().count() + 1)
+        .max(8);
+
+    let chars: Vec<char> = raw_line.chars().collect();
+    if chars.len() <= avail {
+        lines.push(Line::from(Span::styled(
+            format!("{prefix}{raw_line}"),
+            style,
+        )));
+        return;
+    }
+
+    let lead: String = chars.iter().take_while(|c| c.is_whitespace()).collect();
+    let cont_prefix = format!("{prefix}{lead}\u{21AA} ");
+    let cont_avail = (width as usize)
+        .saturating_sub(cont_prefix.chars().count() + 1)
+        .max(8);
+
+    let first: String = chars[..avail].iter().collect();
+    lines.push(Line::from(Span::styled(format!("{prefix}{first}"), style)));
+
+    let mut pos = avail;
+    while pos < chars.len() {
+        let end = (pos + cont_avail).min(chars.len());
+        let chunk: String = chars[pos..end].iter().collect();
+        lines.push(Line::from(Span::styled(
+            format!("{cont_prefix}{chunk}"),
+            style,
+        )));
+        pos = end;
+    }
+}
+
 /// Parse inline markdown: **bold**, *italic*, `code`, [links](url)
 fn parse_inline_markdown(text: &str) -> Vec<Span<'static>> {
     let mut spans = Vec::new();
@@ -288,8 +327,15 @@ fn try_parse_numbered_list(s: &str) -> Option<&str> {
     }
 }
 
-/// Render a spinner character based on tick count
-pub fn spinner(tick: u64) -> &'static str {
-    const FRAMES: &[&str] = &["\u{280B}", "\u{2819}", "\u{2839}", "\u{2838}", "\u{283C}", "\u{2834}", "\u{2826}", "\u{2827}", "\u{2807}", "\u{280F}"];
-    FRAMES[(tick as usize) % FRAMES.len()]
+/// Render a spinner character based on tick count and the configured style
+pub fn spinner(tick: u64, style: crate::core::config::SpinnerStyle) -> &'static str {
+    use crate::core::config::SpinnerStyle;
+    const BRAILLE: &[&str] = &["\u{280B}", "\u{2819}", "\u{2839}", "\u{2838}", "\u{283C}", "\u{2834}", "\u{2826}", "\u{2827}", "\u{2807}", "\u{280F}"];
+    const DOTS: &[&str] = &["\u{00B7}", "\u{2022}", "\u{25CF}", "\u{2022}"];
+    match style {
+        SpinnerStyle::Braille => BRAILLE[(tick as usize) % BRAILLE.len()],
+        SpinnerStyle::Dots => DOTS[(tick as usize) % DOTS.len()],
+        // Static marker: no per-tick redraw churn for screen readers
+        SpinnerStyle::None => "*",
+    }
 }
@@ -13,7 +13,7 @@ use ratatui::{
     prelude::CrosstermBackend,
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
     Frame, Terminal,
 };
 use std::io;
@@ -41,6 +41,14 @@ const CYAN: Color = Color::Rgb(80, 200, 220);
 const RED: Color = Color::Rgb(230, 80, 80);
 const TOOL_FG: Color = Color::Rgb(200, 160, 60);
 
+// ─── Sidebar ─────────────────────────────────────────
+
+/// Below this terminal width the sidebar is drawn as an overlay
+/// instead of a docked column
+const SIDEBAR_DOCK_MIN_WIDTH: u16 = 60;
+const SIDEBAR_MIN_WIDTH: u16 = 16;
+const SIDEBAR_MAX_WIDTH: u16 = 60;
+
 // ─── Types ───────────────────────────────────────────
 
 #[derive(Clone)]
@@ -62,6 +70,7 @@ enum ActiveDialog {
     Model(ModelDialog),
     Session(SessionDialog),
     Command(CommandDialog),
+    Diff(DiffDialog),
     Help,
 }
 
@@ -80,22 +89,56 @@ struct TuiApp {
     input_cursor: usize,
     scroll_offset: u16,
     total_content_height: u16,
+    /// Total chat lines from the last render, for resize recalculation
+    chat_line_count: u16,
     is_streaming: bool,
     current_stream_text: String,
     model_name: String,
     model_id: String,
     total_tokens: (u64, u64),
+    /// Cache-read input tokens this TUI session, for the `/cost` breakdown
+    total_cached_tokens: u64,
     total_cost: f64,
+    /// Usage already counted into totals for the in-flight run
+    /// (input, output, cache-read)
+    run_usage: (u64, u64, u64),
+    /// Deltas not yet flushed to the DB; written incrementally so
+    /// concurrent session writers don't lose updates
+    pending_tokens: (u64, u64),
+    pending_cost: f64,
+    pending_messages: u64,
+    /// Temperature to restore after a one-shot `!temp=` override
+    restore_temp: Option<Option<f64>>,
+    /// tool_choice to restore after a one-shot `/notools` turn
+    restore_tool_choice: Option<Option<crate::core::provider::ToolChoice>>,
+    /// Last submitted user message, for `/retry` and `/edit`
+    last_user_input: Option<String>,
+    /// Set while the last message is loaded in the input for editing; the
+    /// next submit replaces the superseded turn instead of appending
+    pending_edit: bool,
     should_quit: bool,
     status_message: String,
     active_dialog: Option<ActiveDialog>,
     pending_permission: Option<PendingPermission>,
     perm_rx: mpsc::Receiver<PermissionReq>,
     agent_rx: Option<mpsc::Receiver<AgentEvent>>,
+    /// In-flight `/compact` summarization: carries the summary text and
+    /// the number of messages it replaced, or `None` when it failed
+    compact_rx: Option<mpsc::Receiver<Option<(String, usize)>>>,
     cancel_token: Option<tokio_util::sync::CancellationToken>,
     show_sidebar: bool,
+    sidebar_width: u16,
     changed_files: Vec<String>,
+    /// Accumulated unified diffs per changed file, in `changed_files`
+    /// order, for the Ctrl+F diff viewer. Not persisted across restarts
+    file_diffs: std::collections::HashMap<String, String>,
+    /// Context-file mtimes from when the system prompt was last built,
+    /// for detecting mid-session edits to project instructions
+    context_signature: Vec<(String, Option<std::time::SystemTime>)>,
     tick: u64,
+    /// Session stats changed since the last debounced auto-save
+    needs_save: bool,
+    last_saved_tick: u64,
 }
 
 impl TuiApp {
@@ -106,6 +149,16 @@ impl TuiApp {
     ) -> Self {
         let model_name = app.agent.model_name().to_string();
         let model_id = app.agent.model_id().to_string();
+        let sidebar_width = app
+            .config
+            .ui
+            .sidebar_width
+            .clamp(SIDEBAR_MIN_WIDTH, SIDEBAR_MAX_WIDTH);
+        let context_signature = crate::agent::prompt::context_signature(
+            &app.config.working_dir,
+            &app.config.context_paths,
+        );
+        let changed_files = session.changed_files.clone();
         Self {
             app,
             session,
@@ -114,22 +167,38 @@ impl TuiApp {
             input_cursor: 0,
             scroll_offset: 0,
             total_content_height: 0,
+            chat_line_count: 0,
             is_streaming: false,
             current_stream_text: String::new(),
             model_name,
             model_id,
             total_tokens: (0, 0),
+            total_cached_tokens: 0,
             total_cost: 0.0,
+            run_usage: (0, 0, 0),
+            pending_tokens: (0, 0),
+            pending_cost: 0.0,
+            pending_messages: 0,
+            restore_temp: None,
+            restore_tool_choice: None,
+            last_user_input: None,
+            pending_edit: false,
             should_quit: false,
             status_message: "Ready".into(),
             active_dialog: None,
             pending_permission: None,
             perm_rx,
             agent_rx: None,
+            compact_rx: None,
             cancel_token: None,
             show_sidebar: false,
-            changed_files: Vec::new(),
+            sidebar_width,
+            changed_files,
+            file_diffs: std::collections::HashMap::new(),
+            context_signature,
             tick: 0,
+            needs_save: false,
+            last_saved_tick: 0,
         }
     }
 
@@ -138,6 +207,41 @@ impl TuiApp {
     }
 }
 
+// ─── Terminal Guard ──────────────────────────────────
+
+/// Enters raw mode + the alternate screen, and restores the terminal on
+/// drop so a panic or early error never leaves it garbled.
+pub(crate) struct TerminalGuard;
+
+impl TerminalGuard {
+    pub(crate) fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+
+        // Restore the terminal before the default panic output so the
+        // message is readable and the shell isn't left in raw mode
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+        let _ = std::panic::take_hook();
+    }
+}
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+}
+
 // ─── Entry Point ─────────────────────────────────────
 
 pub async fn run(
@@ -152,30 +256,56 @@ pub async fn run(
             .get(&id)
             .await
             .map_err(|e| anyhow::anyhow!("{e}"))?,
-        None => {
-            let s = crate::core::session::Session::new("New session".into());
-            app.db
-                .sessions()
-                .create(&s)
-                .await
-                .map_err(|e| anyhow::anyhow!("{e}"))?;
-            s
-        }
+        None => match offer_resume(&app).await {
+            Some(s) => s,
+            None => {
+                let s = crate::core::session::Session::new("New session".into());
+                app.db
+                    .sessions()
+                    .create(&s)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{e}"))?;
+                s
+            }
+        },
     };
 
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    let _guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     let mut tui_app = TuiApp::new(app, session, perm_rx);
-    let result = run_event_loop(&mut terminal, &mut tui_app).await;
+    if tui_app.session.message_count > 0 {
+        load_session_messages(&mut tui_app).await;
+    }
+    run_event_loop(&mut terminal, &mut tui_app).await
+}
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-    result
+/// Offer to resume the most recent session (crash recovery). Runs before
+/// the alternate screen so a plain y/N prompt works. Returns `None` to
+/// start fresh.
+async fn offer_resume(app: &super::App) -> Option<crate::core::session::Session> {
+    let sessions = app.db.sessions().list().await.ok()?;
+    let latest = sessions.into_iter().find(|s| s.message_count > 0)?;
+    // Only offer recent work, not a weeks-old session
+    let age = chrono::Utc::now().signed_duration_since(latest.updated_at);
+    if age.num_hours() >= 24 {
+        return None;
+    }
+
+    eprint!(
+        "Resume last session \x1b[36m{}\x1b[0m ({} msgs, {})? [y/N] ",
+        latest.title,
+        latest.message_count,
+        &latest.id[..8.min(latest.id.len())],
+    );
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok()?;
+    if answer.trim().eq_ignore_ascii_case("y") {
+        Some(latest)
+    } else {
+        None
+    }
 }
 
 // ─── Event Loop ──────────────────────────────────────
@@ -187,6 +317,15 @@ async fn recv_agent(rx: &mut Option<mpsc::Receiver<AgentEvent>>) -> Option<Agent
     }
 }
 
+async fn recv_compact(
+    rx: &mut Option<mpsc::Receiver<Option<(String, usize)>>>,
+) -> Option<(String, usize)> {
+    match rx {
+        Some(rx) => rx.recv().await.flatten(),
+        None => std::future::pending().await,
+    }
+}
+
 async fn run_event_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut TuiApp,
@@ -207,6 +346,10 @@ async fn run_event_loop(
                 handle_agent_event(app, event).await;
             }
 
+            outcome = recv_compact(&mut app.compact_rx), if app.compact_rx.is_some() => {
+                finish_compact(app, outcome).await;
+            }
+
             Some(perm) = app.perm_rx.recv(), if app.pending_permission.is_none() => {
                 app.pending_permission = Some(PendingPermission {
                     dialog: PermissionDialog::new(&perm.request),
@@ -215,18 +358,42 @@ async fn run_event_loop(
             }
 
             Some(Ok(event)) = event_reader.next() => {
-                if let Event::Key(key) = event {
-                    handle_key_event(app, key).await;
+                match event {
+                    Event::Key(key) => handle_key_event(app, key).await,
+                    Event::Resize(_, rows) => handle_resize(app, rows),
+                    _ => {}
                 }
             }
 
             _ = tick_interval.tick() => {
                 app.tick += 1;
+                // Debounced auto-save so a crash mid-conversation doesn't
+                // lose the latest stats (80ms ticks → save at most every ~2s)
+                if app.needs_save && app.tick.saturating_sub(app.last_saved_tick) >= 25 {
+                    app.needs_save = false;
+                    app.last_saved_tick = app.tick;
+                    let _ = persist_session(app).await;
+                }
             }
         }
     }
 }
 
+/// Recompute scroll bounds as soon as the terminal is resized so the next
+/// draw doesn't briefly render with stale geometry
+fn handle_resize(app: &mut TuiApp, rows: u16) {
+    // Chat pane height: total rows minus header (2), input (3) and status
+    // (1), minus the chat block's own top/bottom borders
+    let visible = rows.saturating_sub(2 + 3 + 1).saturating_sub(2);
+    let was_at_bottom = app.scroll_offset >= app.total_content_height;
+    app.total_content_height = app.chat_line_count.saturating_sub(visible);
+    if was_at_bottom {
+        app.scroll_offset = app.total_content_height;
+    } else {
+        app.scroll_offset = app.scroll_offset.min(app.total_content_height);
+    }
+}
+
 // ─── Agent Events ────────────────────────────────────
 
 async fn handle_agent_event(app: &mut TuiApp, event: AgentEvent) {
@@ -253,12 +420,34 @@ async fn handle_agent_event(app: &mut TuiApp, event: AgentEvent) {
             tool_name,
             result,
             is_error,
+            metadata,
+            affected_paths,
             ..
         } => {
             if !is_error {
-                if let Some(path) = extract_file_path(&tool_name, &result) {
+                // Prefer the paths the tool reported over the brittle
+                // string matching on the result text
+                let paths = if affected_paths.is_empty() {
+                    extract_file_path(&tool_name, &result).into_iter().collect()
+                } else {
+                    affected_paths
+                };
+                if let Some(diff) = metadata.as_ref().and_then(|m| m["diff"].as_str()) {
+                    if !diff.is_empty() {
+                        if let Some(path) = paths.first() {
+                            let entry = app.file_diffs.entry(path.clone()).or_default();
+                            if !entry.is_empty() {
+                                entry.push('\n');
+                            }
+                            entry.push_str(diff);
+                        }
+                    }
+                }
+                for path in paths {
                     if !app.changed_files.contains(&path) {
                         app.changed_files.push(path);
+                        app.session.changed_files = app.changed_files.clone();
+                        app.needs_save = true;
                         if !app.show_sidebar {
                             app.show_sidebar = true;
                         }
@@ -282,30 +471,59 @@ async fn handle_agent_event(app: &mut TuiApp, event: AgentEvent) {
             });
             app.scroll_to_bottom();
         }
-        AgentEvent::Complete { usage, .. } => {
+        AgentEvent::UsageUpdate { cumulative_usage } => {
+            apply_run_usage(app, &cumulative_usage);
+            app.needs_save = true;
+        }
+        AgentEvent::RateLimit { status } => {
+            let wait_s = status.backoff_remaining_ms as f64 / 1000.0;
+            app.status_message = if status.under_pressure() {
+                format!(
+                    "Rate limited ({}/{} recent requests) — provider quota may be exhausted. \
+                     Retrying in {wait_s:.0}s...",
+                    status.limited_recent, status.window,
+                )
+            } else {
+                format!("Rate limited. Retrying in {wait_s:.0}s...")
+            };
+        }
+        AgentEvent::Complete { message, usage, .. } => {
             if !app.current_stream_text.is_empty() {
                 app.messages.push(ChatMessage {
                     role: ChatRole::Assistant,
                     content: std::mem::take(&mut app.current_stream_text),
                 });
             }
-            app.total_tokens.0 += usage.input_tokens;
-            app.total_tokens.1 += usage.output_tokens;
-            if let Some(m) = crate::core::model::get_model(app.app.agent.model_id()) {
-                app.total_cost += m.calculate_cost(usage.input_tokens, usage.output_tokens);
+            apply_run_usage(app, &usage);
+            if let Some(prev) = app.restore_temp.take() {
+                app.app.agent.set_temperature(prev);
+            }
+            if let Some(prev) = app.restore_tool_choice.take() {
+                app.app.agent.set_tool_choice(prev);
             }
             app.status_message = "Ready".into();
             app.is_streaming = false;
             app.agent_rx = None;
             app.cancel_token = None;
             app.scroll_to_bottom();
-            let _ = persist_session(&app.app, &app.session, &app.total_tokens, app.total_cost).await;
+            if let Err(e) = app.app.db.messages().create(&message).await {
+                tracing::warn!("Failed to persist assistant message: {e}");
+            }
+            app.session.message_count += 1;
+            app.pending_messages += 1;
+            let _ = persist_session(app).await;
         }
         AgentEvent::Error { error } => {
             app.messages.push(ChatMessage {
                 role: ChatRole::Error,
                 content: error,
             });
+            if let Some(prev) = app.restore_temp.take() {
+                app.app.agent.set_temperature(prev);
+            }
+            if let Some(prev) = app.restore_tool_choice.take() {
+                app.app.agent.set_tool_choice(prev);
+            }
             app.status_message = "Error".into();
             app.is_streaming = false;
             app.agent_rx = None;
@@ -316,6 +534,29 @@ async fn handle_agent_event(app: &mut TuiApp, event: AgentEvent) {
     }
 }
 
+/// Fold run-cumulative usage into the session totals, counting only the
+/// delta since the last update for this run
+fn apply_run_usage(app: &mut TuiApp, cumulative: &crate::core::message::TokenUsage) {
+    let delta_in = cumulative.input_tokens.saturating_sub(app.run_usage.0);
+    let delta_out = cumulative.output_tokens.saturating_sub(app.run_usage.1);
+    let delta_cached = cumulative.cache_read_tokens.saturating_sub(app.run_usage.2);
+    app.run_usage = (
+        cumulative.input_tokens,
+        cumulative.output_tokens,
+        cumulative.cache_read_tokens,
+    );
+    app.total_tokens.0 += delta_in;
+    app.total_tokens.1 += delta_out;
+    app.total_cached_tokens += delta_cached;
+    app.pending_tokens.0 += delta_in;
+    app.pending_tokens.1 += delta_out;
+    if let Some(m) = crate::core::model::get_model(app.app.agent.model_id()) {
+        let cost = m.calculate_cost(delta_in, delta_cached, delta_out);
+        app.total_cost += cost;
+        app.pending_cost += cost;
+    }
+}
+
 // ─── Key Handling ────────────────────────────────────
 
 async fn handle_key_event(app: &mut TuiApp, key: crossterm::event::KeyEvent) {
@@ -378,6 +619,11 @@ async fn handle_key_event(app: &mut TuiApp, key: crossterm::event::KeyEvent) {
                 }
                 _ => {}
             },
+            ActiveDialog::Diff(d) => {
+                if let DiffDialogAction::Close = d.handle_key(key) {
+                    app.active_dialog = None;
+                }
+            }
             ActiveDialog::Help => {
                 app.active_dialog = None;
             }
@@ -392,6 +638,7 @@ async fn handle_key_event(app: &mut TuiApp, key: crossterm::event::KeyEvent) {
                 if let Some(c) = &app.cancel_token { c.cancel(); }
                 app.is_streaming = false;
                 app.agent_rx = None;
+                app.compact_rx = None;
                 app.cancel_token = None;
                 app.status_message = "Cancelled".into();
                 app.messages.push(ChatMessage { role: ChatRole::System, content: "(cancelled)".into() });
@@ -416,9 +663,26 @@ async fn handle_key_event(app: &mut TuiApp, key: crossterm::event::KeyEvent) {
         (KeyCode::Char('b'), KeyModifiers::CONTROL) => {
             app.show_sidebar = !app.show_sidebar;
         }
+        (KeyCode::Char('f'), KeyModifiers::CONTROL) if !app.is_streaming => {
+            open_diff_dialog(app, None);
+        }
+        (KeyCode::Left, KeyModifiers::CONTROL) if app.show_sidebar => {
+            app.sidebar_width = (app.sidebar_width + 2).min(SIDEBAR_MAX_WIDTH);
+            save_sidebar_width(app.sidebar_width);
+        }
+        (KeyCode::Right, KeyModifiers::CONTROL) if app.show_sidebar => {
+            app.sidebar_width = app.sidebar_width.saturating_sub(2).max(SIDEBAR_MIN_WIDTH);
+            save_sidebar_width(app.sidebar_width);
+        }
         (KeyCode::Char('l'), KeyModifiers::CONTROL) if !app.is_streaming => {
             compact_conversation(app).await;
         }
+        (KeyCode::Char('r'), KeyModifiers::CONTROL) if !app.is_streaming => {
+            retry_last_message(app).await;
+        }
+        (KeyCode::Char('g'), KeyModifiers::CONTROL) if !app.is_streaming => {
+            edit_last_message(app);
+        }
         // Submit
         (KeyCode::Enter, KeyModifiers::NONE) if !app.is_streaming => {
             if !app.input.trim().is_empty() {
@@ -480,7 +744,15 @@ async fn handle_command(app: &mut TuiApp, input: &str) {
         "/cost" => {
             app.messages.push(ChatMessage {
                 role: ChatRole::System,
-                content: format!("Tokens: {} in / {} out | Cost: ${:.4}", app.total_tokens.0, app.total_tokens.1, app.total_cost),
+                content: format!(
+                    "Tokens: {} in ({} cached) / {} out | Cost: ${:.4} | Model: {} ({})",
+                    app.total_tokens.0,
+                    app.total_cached_tokens,
+                    app.total_tokens.1,
+                    app.total_cost,
+                    app.model_id,
+                    provider_name(&app.app.config),
+                ),
             });
             app.scroll_to_bottom();
         }
@@ -490,7 +762,36 @@ async fn handle_command(app: &mut TuiApp, input: &str) {
             let _ = app.app.db.messages().delete_session_messages(&app.session.id).await;
         }
         "/compact" => { compact_conversation(app).await; }
+        "/reload-context" => { reload_context(app); }
+        "/retry" => { retry_last_message(app).await; }
+        "/edit" => { edit_last_message(app); }
         "/sidebar" => { app.show_sidebar = !app.show_sidebar; }
+        "/diff" => { open_diff_dialog(app, input.split_whitespace().nth(1)); }
+        "/set" => {
+            let content = handle_set_command(app, input);
+            app.messages.push(ChatMessage { role: ChatRole::System, content });
+            app.scroll_to_bottom();
+        }
+        "/system" => {
+            let content = handle_system_command(app, input);
+            app.messages.push(ChatMessage { role: ChatRole::System, content });
+            app.scroll_to_bottom();
+        }
+        "/notools" => {
+            let prompt = input.strip_prefix("/notools").unwrap_or("").trim().to_string();
+            if prompt.is_empty() {
+                app.messages.push(ChatMessage {
+                    role: ChatRole::System,
+                    content: "Usage: /notools <prompt> — answer without calling any tools.".into(),
+                });
+                app.scroll_to_bottom();
+            } else {
+                // One-shot: forbid tool calls for this turn only
+                app.restore_tool_choice = Some(app.app.agent.tool_choice());
+                app.app.agent.set_tool_choice(Some(crate::core::provider::ToolChoice::None));
+                submit_message(app, prompt).await;
+            }
+        }
         _ => {
             app.messages.push(ChatMessage {
                 role: ChatRole::System,
@@ -501,13 +802,191 @@ async fn handle_command(app: &mut TuiApp, input: &str) {
     }
 }
 
+/// Handle `/set <param> <value>` for runtime provider parameters.
+/// `default` as the value restores the provider default.
+fn handle_set_command(app: &mut TuiApp, input: &str) -> String {
+    let mut parts = input.split_whitespace().skip(1);
+    let (Some(param), Some(value)) = (parts.next(), parts.next()) else {
+        return format!(
+            "Usage: /set <temperature|max_tokens|tool_choice> <value|default>. \
+            Current: temperature={}, max_tokens={}",
+            app.app.agent.temperature().map_or("default".into(), |t| t.to_string()),
+            app.app.agent.max_tokens_override().map_or("default".into(), |m| m.to_string()),
+        );
+    };
+
+    match param {
+        "temperature" | "temp" => {
+            if value == "default" {
+                app.app.agent.set_temperature(None);
+                return "Temperature reset to default.".into();
+            }
+            match value.parse::<f64>() {
+                Ok(t) if (0.0..=2.0).contains(&t) => {
+                    app.app.agent.set_temperature(Some(t));
+                    format!("Temperature set to {t}.")
+                }
+                _ => format!("Invalid temperature '{value}' (expected 0.0-2.0 or 'default')."),
+            }
+        }
+        "max_tokens" => {
+            if value == "default" {
+                app.app.agent.set_max_tokens_override(None);
+                return "max_tokens reset to default.".into();
+            }
+            match value.parse::<u64>() {
+                Ok(m) if m > 0 => {
+                    app.app.agent.set_max_tokens_override(Some(m));
+                    format!("max_tokens set to {m}.")
+                }
+                _ => format!("Invalid max_tokens '{value}' (expected a positive integer)."),
+            }
+        }
+        "tool_choice" => {
+            use crate::core::provider::ToolChoice;
+            let choice = match value {
+                "default" | "auto" => None,
+                "none" => Some(ToolChoice::None),
+                "required" => Some(ToolChoice::Required),
+                name => Some(ToolChoice::Tool(name.to_string())),
+            };
+            app.app.agent.set_tool_choice(choice);
+            format!("tool_choice set to {value}.")
+        }
+        _ => format!("Unknown parameter '{param}'. Supported: temperature, max_tokens, tool_choice."),
+    }
+}
+
+/// Handle `/system` — inspect or modify the agent's system prompt.
+/// No argument shows the current prompt, `reset` rebuilds it from the
+/// context files, and any other text is appended as session instructions.
+fn handle_system_command(app: &mut TuiApp, input: &str) -> String {
+    let rest = input.strip_prefix("/system").unwrap_or("").trim();
+
+    if rest.is_empty() {
+        let prompt = app.app.agent.system_prompt();
+        return format!("System prompt ({} chars):\n{}", prompt.len(), prompt);
+    }
+
+    if rest == "reset" {
+        let prompt = crate::agent::prompt::build_system_prompt(
+            &app.app.config.working_dir,
+            &app.app.config.context_paths,
+            &app.app.config.include_files,
+        );
+        app.app.agent.set_system_prompt(prompt);
+        app.context_signature = crate::agent::prompt::context_signature(
+            &app.app.config.working_dir,
+            &app.app.config.context_paths,
+        );
+        return "System prompt rebuilt from context files.".into();
+    }
+
+    let updated = format!(
+        "{}\n\n# Session Instructions\n{}",
+        app.app.agent.system_prompt(),
+        rest
+    );
+    app.app.agent.set_system_prompt(updated);
+    format!("Session instructions added ({} chars).", rest.len())
+}
+
+/// Strip an inline `!temp=<value> ` prefix, returning the one-shot
+/// temperature and the remaining prompt
+fn parse_temp_prefix(input: &str) -> (Option<f64>, &str) {
+    if let Some(rest) = input.strip_prefix("!temp=") {
+        if let Some((value, prompt)) = rest.split_once(char::is_whitespace) {
+            if let Ok(t) = value.parse::<f64>() {
+                if (0.0..=2.0).contains(&t) {
+                    return (Some(t), prompt.trim_start());
+                }
+            }
+        }
+    }
+    (None, input)
+}
+
 // ─── Actions ─────────────────────────────────────────
 
+/// Rebuild the system prompt from the context files on disk and apply it
+/// to the agent for subsequent turns
+fn reload_context(app: &mut TuiApp) {
+    let prompt = crate::agent::prompt::build_system_prompt(
+        &app.app.config.working_dir,
+        &app.app.config.context_paths,
+        &app.app.config.include_files,
+    );
+    app.app.agent.set_system_prompt(prompt);
+    app.context_signature = crate::agent::prompt::context_signature(
+        &app.app.config.working_dir,
+        &app.app.config.context_paths,
+    );
+    app.messages.push(ChatMessage {
+        role: ChatRole::System,
+        content: "Project instructions reloaded.".into(),
+    });
+    app.scroll_to_bottom();
+}
+
+/// Warn once when project instruction files changed since the system
+/// prompt was built, so long sessions don't silently run on stale context
+fn check_context_changed(app: &mut TuiApp) {
+    let current = crate::agent::prompt::context_signature(
+        &app.app.config.working_dir,
+        &app.app.config.context_paths,
+    );
+    if current == app.context_signature {
+        return;
+    }
+    let changed: Vec<&str> = current
+        .iter()
+        .zip(app.context_signature.iter())
+        .filter(|(new, old)| new != old)
+        .map(|((path, _), _)| path.as_str())
+        .collect();
+    app.messages.push(ChatMessage {
+        role: ChatRole::System,
+        content: format!(
+            "Context files changed on disk ({}). /reload-context to refresh project instructions.",
+            changed.join(", ")
+        ),
+    });
+    // Remember what we warned about so the notice doesn't repeat every turn
+    app.context_signature = current;
+}
+
 async fn submit_message(app: &mut TuiApp, input: String) {
+    check_context_changed(app);
+
+    // An edited message replaces the superseded turn before regenerating
+    if app.pending_edit {
+        app.pending_edit = false;
+        drop_last_turn(app);
+        let _ = app.app.db.messages().delete_last_turn(&app.session.id).await;
+    }
+
+    // Inline one-shot temperature override: `!temp=0.2 <prompt>`
+    let (one_shot_temp, prompt) = parse_temp_prefix(&input);
+    let input = prompt.to_string();
+    if let Some(t) = one_shot_temp {
+        app.restore_temp = Some(app.app.agent.temperature());
+        app.app.agent.set_temperature(Some(t));
+    }
+
+    app.last_user_input = Some(input.clone());
     app.messages.push(ChatMessage { role: ChatRole::User, content: input.clone() });
     app.scroll_to_bottom();
+    // Persist the user turn up front so a crash mid-run doesn't lose it
+    let user_msg = crate::core::message::Message::new_user(app.session.id.clone(), input.clone());
+    if let Err(e) = app.app.db.messages().create(&user_msg).await {
+        tracing::warn!("Failed to persist user message: {e}");
+    }
+    app.session.message_count += 1;
+    app.pending_messages += 1;
+    app.needs_save = true;
     app.is_streaming = true;
     app.current_stream_text.clear();
+    app.run_usage = (0, 0, 0);
     app.status_message = "Thinking...".into();
     let messages = app.app.db.messages().list(&app.session.id).await.unwrap_or_default();
     let (rx, cancel) = app.app.agent.run(app.session.id.clone(), messages, input);
@@ -521,6 +1000,9 @@ fn switch_model(app: &mut TuiApp, model_id: &crate::core::model::ModelId) {
             app.app.agent.switch_provider(p);
             app.model_name = app.app.agent.model_name().to_string();
             app.model_id = app.app.agent.model_id().to_string();
+            // Record the new model on the session for spend auditing
+            app.session.model_id = Some(app.model_id.clone());
+            app.needs_save = true;
             app.messages.push(ChatMessage {
                 role: ChatRole::System,
                 content: format!("Model: {}", app.model_name),
@@ -538,22 +1020,7 @@ async fn switch_session(app: &mut TuiApp, session_id: &str) {
     match app.app.db.sessions().get(session_id).await {
         Ok(session) => {
             app.session = session;
-            app.messages.clear();
-            app.changed_files.clear();
-            if let Ok(db_msgs) = app.app.db.messages().list(session_id).await {
-                for msg in &db_msgs {
-                    let text = msg.text_content();
-                    if text.is_empty() { continue; }
-                    let role = match msg.role {
-                        crate::core::message::MessageRole::User => ChatRole::User,
-                        crate::core::message::MessageRole::Assistant => ChatRole::Assistant,
-                        crate::core::message::MessageRole::System => ChatRole::System,
-                        crate::core::message::MessageRole::Tool => ChatRole::Tool("tool".into()),
-                    };
-                    app.messages.push(ChatMessage { role, content: text.to_string() });
-                }
-            }
-            app.scroll_to_bottom();
+            load_session_messages(app).await;
         }
         Err(e) => {
             app.messages.push(ChatMessage { role: ChatRole::Error, content: format!("{e}") });
@@ -561,6 +1028,33 @@ async fn switch_session(app: &mut TuiApp, session_id: &str) {
     }
 }
 
+/// Populate the chat view and totals from the current session's stored
+/// messages and stats
+async fn load_session_messages(app: &mut TuiApp) {
+    app.messages.clear();
+    app.changed_files = app.session.changed_files.clone();
+    // Diffs live only in memory; a restored session starts without them
+    app.file_diffs.clear();
+    app.total_tokens = (app.session.prompt_tokens, app.session.completion_tokens);
+    // Cache splits aren't persisted per session, so the breakdown restarts
+    app.total_cached_tokens = 0;
+    app.total_cost = app.session.cost;
+    if let Ok(db_msgs) = app.app.db.messages().list(&app.session.id).await {
+        for msg in &db_msgs {
+            let text = msg.text_content();
+            if text.is_empty() { continue; }
+            let role = match msg.role {
+                crate::core::message::MessageRole::User => ChatRole::User,
+                crate::core::message::MessageRole::Assistant => ChatRole::Assistant,
+                crate::core::message::MessageRole::System => ChatRole::System,
+                crate::core::message::MessageRole::Tool => ChatRole::Tool("tool".into()),
+            };
+            app.messages.push(ChatMessage { role, content: text.to_string() });
+        }
+    }
+    app.scroll_to_bottom();
+}
+
 async fn create_new_session(app: &mut TuiApp) {
     let s = crate::core::session::Session::new("New session".into());
     if let Err(e) = app.app.db.sessions().create(&s).await {
@@ -570,17 +1064,167 @@ async fn create_new_session(app: &mut TuiApp) {
     app.session = s;
     app.messages.clear();
     app.changed_files.clear();
+    app.file_diffs.clear();
     app.total_tokens = (0, 0);
+    app.total_cached_tokens = 0;
     app.total_cost = 0.0;
 }
 
+/// Pop chat messages back through (and including) the most recent user
+/// message, discarding the superseded turn
+fn drop_last_turn(app: &mut TuiApp) {
+    while let Some(msg) = app.messages.last() {
+        let was_user = matches!(msg.role, ChatRole::User);
+        app.messages.pop();
+        if was_user {
+            break;
+        }
+    }
+}
+
+/// Re-submit the last user message, dropping the failed or unsatisfactory
+/// turn (everything from that user message on) from the chat first
+async fn retry_last_message(app: &mut TuiApp) {
+    if app.is_streaming {
+        return;
+    }
+    let Some(input) = app.last_user_input.clone() else {
+        app.messages.push(ChatMessage { role: ChatRole::System, content: "Nothing to retry.".into() });
+        app.scroll_to_bottom();
+        return;
+    };
+    drop_last_turn(app);
+    submit_message(app, input).await;
+}
+
+/// Load the last user message into the input box for editing; submitting
+/// replaces the old turn in memory and the DB, then regenerates
+fn edit_last_message(app: &mut TuiApp) {
+    if app.is_streaming {
+        return;
+    }
+    let Some(prev) = app.last_user_input.clone() else {
+        app.messages.push(ChatMessage { role: ChatRole::System, content: "No message to edit.".into() });
+        app.scroll_to_bottom();
+        return;
+    };
+    app.input_cursor = prev.len();
+    app.input = prev;
+    app.pending_edit = true;
+    app.status_message = "Editing last message".into();
+}
+
+/// Open the diff viewer over the session's changed files, starting on
+/// `start_path` when given (substring match, e.g. from `/diff main.rs`)
+/// and otherwise on the most recently changed file
+fn open_diff_dialog(app: &mut TuiApp, start_path: Option<&str>) {
+    if app.changed_files.is_empty() {
+        app.messages.push(ChatMessage {
+            role: ChatRole::System,
+            content: "No files have been changed this session.".into(),
+        });
+        app.scroll_to_bottom();
+        return;
+    }
+    let files: Vec<(String, String)> = app
+        .changed_files
+        .iter()
+        .map(|p| (p.clone(), app.file_diffs.get(p).cloned().unwrap_or_default()))
+        .collect();
+    let selected = match start_path {
+        Some(wanted) => files
+            .iter()
+            .position(|(p, _)| p.contains(wanted))
+            .unwrap_or(files.len() - 1),
+        None => files.len() - 1,
+    };
+    app.active_dialog = Some(ActiveDialog::Diff(DiffDialog::new(files, selected)));
+}
+
 async fn compact_conversation(app: &mut TuiApp) {
-    if app.messages.len() <= 5 {
+    let keep = app.app.config.agent.compact_keep_recent;
+    if app.messages.len() <= keep + 1 {
         app.messages.push(ChatMessage { role: ChatRole::System, content: "Not enough to compact.".into() });
         app.scroll_to_bottom();
         return;
     }
-    let keep = 4;
+
+    // Summarize through the fast model; fall back to the lossy local
+    // compaction when no provider can be built at all
+    let provider = match crate::providers::create_provider_for_role(
+        &app.app.config,
+        crate::providers::ModelRole::Fast,
+    ) {
+        Ok(p) => p,
+        Err(_) => {
+            crude_compact(app).await;
+            return;
+        }
+    };
+
+    let db = app.app.db.clone();
+    let session_id = app.session.id.clone();
+    let (tx, rx) = mpsc::channel(1);
+    tokio::spawn(async move {
+        let _ = tx
+            .send(summarize_session(db, session_id, provider, keep).await)
+            .await;
+    });
+
+    // Block input and show the spinner until the summary lands
+    app.compact_rx = Some(rx);
+    app.is_streaming = true;
+    app.current_stream_text.clear();
+    app.status_message = "Compacting conversation...".into();
+}
+
+/// Background half of `/compact`: summarize the older DB messages through
+/// the fast model and rewrite the session history so the next agent turn
+/// picks up the recap. Returns the summary and how many messages it
+/// replaced, or `None` when summarization failed (history left untouched)
+async fn summarize_session(
+    db: crate::storage::Database,
+    session_id: String,
+    provider: std::sync::Arc<dyn crate::core::provider::Provider>,
+    keep: usize,
+) -> Option<(String, usize)> {
+    let mut msgs = db.messages().list(&session_id).await.ok()?;
+    let removed =
+        crate::agent::agent::compact_messages(&provider, &session_id, &mut msgs, keep)
+            .await
+            .ok()?;
+    if removed == 0 {
+        return None;
+    }
+
+    let summary = msgs.first().map(|m| m.text_content()).unwrap_or_default();
+    db.messages().delete_session_messages(&session_id).await.ok()?;
+    for msg in &msgs {
+        db.messages().create(msg).await.ok()?;
+    }
+    Some((summary, removed))
+}
+
+async fn finish_compact(app: &mut TuiApp, outcome: Option<(String, usize)>) {
+    app.compact_rx = None;
+    app.is_streaming = false;
+    match outcome {
+        Some((summary, removed)) => {
+            let keep = app.app.config.agent.compact_keep_recent;
+            let to_remove = app.messages.len().saturating_sub(keep);
+            app.messages.drain(..to_remove);
+            app.messages.insert(0, ChatMessage { role: ChatRole::System, content: summary });
+            app.status_message = format!("Compacted {removed} messages");
+        }
+        None => crude_compact(app).await,
+    }
+    app.scroll_to_bottom();
+}
+
+/// Lossy fallback when summarization isn't available: first 150 chars of
+/// each older message, and the DB history is dropped entirely
+async fn crude_compact(app: &mut TuiApp) {
+    let keep = app.app.config.agent.compact_keep_recent;
     let to_compact = app.messages.len().saturating_sub(keep);
     let mut parts = Vec::new();
     for msg in app.messages.drain(..to_compact) {
@@ -597,24 +1241,66 @@ async fn compact_conversation(app: &mut TuiApp) {
     app.messages.insert(0, ChatMessage { role: ChatRole::System, content: summary });
     let _ = app.app.db.messages().delete_session_messages(&app.session.id).await;
     app.status_message = format!("Compacted {to_compact} messages");
-    app.scroll_to_bottom();
 }
 
-async fn persist_session(
-    inner: &super::App,
-    session: &crate::core::session::Session,
-    tokens: &(u64, u64),
-    cost: f64,
-) -> Result<()> {
-    let mut s = session.clone();
-    s.prompt_tokens = tokens.0;
-    s.completion_tokens = tokens.1;
-    s.cost = cost;
-    s.message_count += 1;
-    inner.db.sessions().update(&s).await.map_err(|e| anyhow::anyhow!("{e}"))
+/// Flush unsaved usage as DB-level increments, then write the session
+/// metadata. Incremental counter updates mean concurrent writers to the
+/// same row (auto-save racing a completion) can't clobber each other.
+async fn persist_session(app: &mut TuiApp) -> Result<()> {
+    let (dp, dc) = app.pending_tokens;
+    let cost = app.pending_cost;
+    let msgs = app.pending_messages;
+    if dp > 0 || dc > 0 || cost > 0.0 || msgs > 0 {
+        app.app
+            .db
+            .sessions()
+            .add_usage(&app.session.id, dp, dc, cost, msgs)
+            .await
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+        app.pending_tokens = (0, 0);
+        app.pending_cost = 0.0;
+        app.pending_messages = 0;
+    }
+
+    let mut s = app.session.clone();
+    s.model_id = Some(app.app.agent.model_id().0.clone());
+    s.provider = Some(provider_name(&app.app.config).to_string());
+    app.app
+        .db
+        .sessions()
+        .update_meta(&s)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))
 }
 
-fn extract_file_path(tool_name: &str, result: &str) -> Option<String> {
+fn provider_name(config: &crate::core::config::AppConfig) -> &'static str {
+    match config.provider_type {
+        crate::core::config::ProviderType::AtlasCloud => "atlas_cloud",
+        crate::core::config::ProviderType::OpenRouter => "open_router",
+        crate::core::config::ProviderType::Anthropic => "anthropic",
+    }
+}
+
+/// Persist the sidebar width to the global config file (best effort; a
+/// failure here shouldn't interrupt the session)
+fn save_sidebar_width(width: u16) {
+    let Some(dir) = dirs::config_dir().map(|c| c.join("octo-code")) else {
+        return;
+    };
+    let path = dir.join("config.json");
+    let mut config: serde_json::Value = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    config["ui"]["sidebar_width"] = serde_json::json!(width);
+    if std::fs::create_dir_all(&dir).is_ok() {
+        if let Ok(content) = serde_json::to_string_pretty(&config) {
+            let _ = std::fs::write(&path, content);
+        }
+    }
+}
+
+pub(crate) fn extract_file_path(tool_name: &str, result: &str) -> Option<String> {
     match tool_name {
         "write" => result.split(" to ").nth(1).map(|s| s.trim().to_string()),
         "edit" => result.strip_prefix("Edited ").and_then(|s| s.split('.').next()).map(|s| s.trim().to_string()),
@@ -628,10 +1314,14 @@ fn ui(f: &mut Frame, app: &mut TuiApp) {
     let area = f.area();
     f.render_widget(Paragraph::new("").style(Style::default().bg(BG)), area);
 
-    let (main_area, sidebar_area) = if app.show_sidebar && area.width > 60 {
+    // Dock the sidebar on wide terminals; on narrow ones it becomes an
+    // overlay so toggling it never makes it vanish entirely
+    let docked = app.show_sidebar && area.width > SIDEBAR_DOCK_MIN_WIDTH;
+    let (main_area, sidebar_area) = if docked {
+        let width = app.sidebar_width.min(area.width.saturating_sub(40));
         let c = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Min(40), Constraint::Length(24)])
+            .constraints([Constraint::Min(40), Constraint::Length(width)])
             .split(area);
         (c[0], Some(c[1]))
     } else {
@@ -652,16 +1342,28 @@ fn ui(f: &mut Frame, app: &mut TuiApp) {
     render_chat(f, app, chunks[1]);
     render_input(f, app, chunks[2]);
     render_status(f, app, chunks[3]);
-    if let Some(sb) = sidebar_area { render_sidebar(f, app, sb); }
+    if let Some(sb) = sidebar_area {
+        render_sidebar(f, app, sb, false);
+    } else if app.show_sidebar {
+        let width = app.sidebar_width.min(area.width.saturating_sub(4));
+        let overlay = Rect::new(
+            area.right().saturating_sub(width),
+            chunks[1].y,
+            width,
+            chunks[1].height,
+        );
+        render_sidebar(f, app, overlay, true);
+    }
 
     // Overlays
     if let Some(perm) = &app.pending_permission {
         perm.dialog.render(f, area);
-    } else if let Some(d) = &app.active_dialog {
+    } else if let Some(d) = &mut app.active_dialog {
         match d {
             ActiveDialog::Model(d) => d.render(f, area),
             ActiveDialog::Session(d) => d.render(f, area),
             ActiveDialog::Command(d) => d.render(f, area),
+            ActiveDialog::Diff(d) => d.render(f, area),
             ActiveDialog::Help => HelpDialog::render(f, area),
         }
     }
@@ -714,7 +1416,7 @@ fn render_chat(f: &mut Frame, app: &mut TuiApp, area: Rect) {
                         Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
                     ),
                 ]));
-                let md_lines = markdown::render_markdown(&msg.content, "    ");
+                let md_lines = markdown::render_markdown(&msg.content, "    ", area.width);
                 lines.extend(md_lines);
             }
             ChatRole::Tool(name) => {
@@ -762,14 +1464,16 @@ fn render_chat(f: &mut Frame, app: &mut TuiApp, area: Rect) {
             Span::styled("  \u{2728} ", Style::default().fg(ACCENT)),
             Span::styled("Assistant", Style::default().fg(ACCENT).add_modifier(Modifier::BOLD)),
         ]));
-        let md_lines = markdown::render_markdown(&app.current_stream_text, "    ");
+        let md_lines = markdown::render_markdown(&app.current_stream_text, "    ", area.width);
         lines.extend(md_lines);
-        lines.push(Line::from(Span::styled(
-            "    \u{2588}",
-            Style::default().fg(ACCENT),
-        )));
+        if app.app.config.ui.spinner != crate::core::config::SpinnerStyle::None {
+            lines.push(Line::from(Span::styled(
+                "    \u{2588}",
+                Style::default().fg(ACCENT),
+            )));
+        }
     } else if app.is_streaming {
-        let spin = markdown::spinner(app.tick);
+        let spin = markdown::spinner(app.tick, app.app.config.ui.spinner);
         lines.push(Line::from(Span::styled(
             format!("  {spin} {}", app.status_message),
             Style::default().fg(YELLOW),
@@ -777,6 +1481,7 @@ fn render_chat(f: &mut Frame, app: &mut TuiApp, area: Rect) {
     }
 
     let total = lines.len() as u16;
+    app.chat_line_count = total;
     let visible = area.height.saturating_sub(2);
     app.total_content_height = total.saturating_sub(visible);
     if app.is_streaming { app.scroll_offset = app.total_content_height; }
@@ -810,7 +1515,10 @@ fn render_input(f: &mut Frame, app: &TuiApp, area: Rect) {
     };
 
     let title = if app.is_streaming {
-        format!(" {} Streaming... Ctrl+C cancel ", markdown::spinner(app.tick))
+        format!(
+            " {} Streaming... Ctrl+C cancel ",
+            markdown::spinner(app.tick, app.app.config.ui.spinner)
+        )
     } else {
         " Message \u{2502} Enter send \u{2502} Ctrl+K cmds ".to_string()
     };
@@ -847,7 +1555,24 @@ fn render_status(f: &mut Frame, app: &TuiApp, area: Rect) {
     f.render_widget(Paragraph::new(line).style(Style::default().bg(SURFACE)), area);
 }
 
-fn render_sidebar(f: &mut Frame, app: &TuiApp, area: Rect) {
+/// Added/removed line counts of a unified diff, for the sidebar badges
+fn diff_stats(diff: &str) -> (usize, usize) {
+    let mut added = 0;
+    let mut removed = 0;
+    for line in diff.lines() {
+        if line.starts_with('+') {
+            added += 1;
+        } else if line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    (added, removed)
+}
+
+fn render_sidebar(f: &mut Frame, app: &TuiApp, area: Rect, overlay: bool) {
+    if overlay {
+        f.render_widget(Clear, area);
+    }
     let mut lines = Vec::new();
     lines.push(Line::from(""));
     if app.changed_files.is_empty() {
@@ -858,14 +1583,25 @@ fn render_sidebar(f: &mut Frame, app: &TuiApp, area: Rect) {
                 .file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_else(|| path.clone());
-            lines.push(Line::from(vec![
+            let mut spans = vec![
                 Span::styled("  + ", Style::default().fg(GREEN)),
                 Span::styled(name, Style::default().fg(TEXT)),
-            ]));
+            ];
+            if let Some(diff) = app.file_diffs.get(path) {
+                let (added, removed) = diff_stats(diff);
+                spans.push(Span::styled(format!(" +{added}"), Style::default().fg(GREEN)));
+                spans.push(Span::styled(format!("-{removed}"), Style::default().fg(RED)));
+            }
+            lines.push(Line::from(spans));
         }
     }
+    let borders = if overlay {
+        Borders::ALL
+    } else {
+        Borders::LEFT | Borders::TOP | Borders::BOTTOM
+    };
     let block = Block::default()
-        .borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM)
+        .borders(borders)
         .border_style(Style::default().fg(BORDER))
         .title(Span::styled(
             format!(" Files ({}) ", app.changed_files.len()),
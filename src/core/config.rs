@@ -10,12 +10,16 @@ const DEFAULT_BASE_URL: &str = "https://api.atlascloud.ai";
 /// OpenRouter base URL
 const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api";
 
+/// Anthropic base URL
+const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+
 /// API provider type
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ProviderType {
     AtlasCloud,
     OpenRouter,
+    Anthropic,
 }
 
 impl Default for ProviderType {
@@ -48,6 +52,10 @@ pub struct AppConfig {
     #[serde(default)]
     pub openrouter_api_key: Option<String>,
 
+    /// Anthropic API key for the native Messages API provider
+    #[serde(default)]
+    pub anthropic_api_key: Option<String>,
+
     /// Base URL for the API gateway (default: Atlas Cloud)
     #[serde(default = "default_base_url")]
     pub base_url: String,
@@ -58,14 +66,29 @@ pub struct AppConfig {
     #[serde(default)]
     pub shell: ShellConfig,
 
+    #[serde(default)]
+    pub tools: ToolsConfig,
+
     #[serde(default = "default_context_paths")]
     pub context_paths: Vec<String>,
 
+    /// Source files injected into every session as read-only context
+    /// (e.g. a types module the model always needs), distinct from the
+    /// instruction files in `context_paths`
+    #[serde(default)]
+    pub include_files: Vec<String>,
+
     #[serde(default)]
     pub debug: bool,
 
     #[serde(default)]
     pub coderlm: CoderlmConfig,
+
+    #[serde(default)]
+    pub ui: UiConfig,
+
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 fn default_base_url() -> String {
@@ -81,12 +104,17 @@ impl Default for AppConfig {
             api_keys: vec![],
             provider_type: ProviderType::default(),
             openrouter_api_key: None,
+            anthropic_api_key: None,
             base_url: default_base_url(),
             agent: AgentConfig::default(),
             shell: ShellConfig::default(),
+            tools: ToolsConfig::default(),
             context_paths: default_context_paths(),
+            include_files: vec![],
             debug: false,
             coderlm: CoderlmConfig::default(),
+            ui: UiConfig::default(),
+            http: HttpConfig::default(),
         }
     }
 }
@@ -130,6 +158,45 @@ pub struct AgentConfig {
     pub max_tokens: u64,
     #[serde(default)]
     pub reasoning_effort: Option<String>,
+
+    /// Automatically compact the conversation when the estimated prompt
+    /// exceeds `auto_compact_threshold` of the model's context window
+    #[serde(default)]
+    pub auto_compact: bool,
+
+    /// Fraction of the context window that triggers auto-compaction
+    #[serde(default = "default_auto_compact_threshold")]
+    pub auto_compact_threshold: f64,
+
+    /// Recent messages kept verbatim when compacting the conversation
+    #[serde(default = "default_keep_recent")]
+    pub compact_keep_recent: usize,
+
+    /// Recent messages kept when trimming to fit the context window
+    #[serde(default = "default_keep_recent")]
+    pub trim_keep_recent: usize,
+
+    /// Summarize oversized tool results through the fast model instead of
+    /// head-truncating them, so details from the tail survive
+    #[serde(default)]
+    pub summarize_tool_results: bool,
+
+    /// Rate-limit retries in the agent loop, on top of the provider's own
+    /// transient-error retries. Each agent attempt waits at least the
+    /// server-suggested delay, so total wait compounds with provider retries;
+    /// set to 0 to fail fast
+    #[serde(default = "default_retry_attempts")]
+    pub retry_attempts: u32,
+
+    /// Escalation factor per agent-level retry: attempt N waits
+    /// `base * (1 + multiplier * N)`. 1.0 gives linear backoff (5s, 10s, 15s)
+    #[serde(default = "default_retry_backoff_multiplier")]
+    pub retry_backoff_multiplier: f64,
+
+    /// Abort a streaming response when no bytes arrive for this long.
+    /// Catches connections that hang mid-stream with TCP still open
+    #[serde(default = "default_stream_idle_timeout_secs")]
+    pub stream_idle_timeout_secs: u64,
 }
 
 fn default_coder_model() -> ModelId {
@@ -152,6 +219,26 @@ fn default_max_tokens() -> u64 {
     16_384
 }
 
+fn default_auto_compact_threshold() -> f64 {
+    0.8
+}
+
+fn default_keep_recent() -> usize {
+    4
+}
+
+fn default_retry_attempts() -> u32 {
+    3
+}
+
+fn default_retry_backoff_multiplier() -> f64 {
+    1.0
+}
+
+fn default_stream_idle_timeout_secs() -> u64 {
+    90
+}
+
 impl Default for AgentConfig {
     fn default() -> Self {
         Self {
@@ -161,6 +248,14 @@ impl Default for AgentConfig {
             long_context_model: default_long_context_model(),
             max_tokens: default_max_tokens(),
             reasoning_effort: None,
+            auto_compact: false,
+            auto_compact_threshold: default_auto_compact_threshold(),
+            compact_keep_recent: default_keep_recent(),
+            trim_keep_recent: default_keep_recent(),
+            summarize_tool_results: false,
+            retry_attempts: default_retry_attempts(),
+            retry_backoff_multiplier: default_retry_backoff_multiplier(),
+            stream_idle_timeout_secs: default_stream_idle_timeout_secs(),
         }
     }
 }
@@ -186,6 +281,56 @@ impl Default for ShellConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolsConfig {
+    /// Max concurrent executions per tool name (e.g. {"coderlm": 3}).
+    /// Calls beyond the limit queue until a slot frees up.
+    #[serde(default = "default_tool_concurrency")]
+    pub concurrency: std::collections::HashMap<String, usize>,
+
+    /// Timeout in seconds applied to any tool without its own entry.
+    /// 0 disables the timeout entirely.
+    #[serde(default = "default_tool_timeout")]
+    pub default_timeout_secs: u64,
+
+    /// Per-tool timeout overrides in seconds (e.g. {"coderlm": 30})
+    #[serde(default)]
+    pub timeout_secs: std::collections::HashMap<String, u64>,
+}
+
+fn default_tool_concurrency() -> std::collections::HashMap<String, usize> {
+    let mut limits = std::collections::HashMap::new();
+    limits.insert("coderlm".to_string(), 3);
+    limits
+}
+
+fn default_tool_timeout() -> u64 {
+    300
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: default_tool_concurrency(),
+            default_timeout_secs: default_tool_timeout(),
+            timeout_secs: std::collections::HashMap::new(),
+        }
+    }
+}
+
+impl ToolsConfig {
+    /// Timeout for the named tool: its own entry, or the global default.
+    /// Returns `None` when the effective value is 0 (disabled).
+    pub fn timeout_for(&self, tool_name: &str) -> Option<std::time::Duration> {
+        let secs = self
+            .timeout_secs
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.default_timeout_secs);
+        (secs > 0).then(|| std::time::Duration::from_secs(secs))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoderlmConfig {
     #[serde(default = "default_coderlm_url")]
@@ -212,6 +357,130 @@ impl Default for CoderlmConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiConfig {
+    /// Sidebar width in columns; resizable at runtime and persisted here
+    #[serde(default = "default_sidebar_width")]
+    pub sidebar_width: u16,
+
+    /// Spinner and streaming-cursor animation style. `None` disables
+    /// animation entirely for screen readers and low-refresh terminals
+    #[serde(default)]
+    pub spinner: SpinnerStyle,
+}
+
+/// Spinner animation preset
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpinnerStyle {
+    #[default]
+    Braille,
+    Dots,
+    None,
+}
+
+fn default_sidebar_width() -> u16 {
+    24
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            sidebar_width: default_sidebar_width(),
+            spinner: SpinnerStyle::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// Proxy URL for all outbound HTTP (e.g. "http://proxy.corp:3128").
+    /// When unset, `HTTPS_PROXY`/`HTTP_PROXY` from the environment apply.
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// Hosts that bypass the proxy. The defaults cover the local CodeRLM
+    /// server so it stays reachable behind a corporate proxy.
+    #[serde(default = "default_no_proxy")]
+    pub no_proxy: Vec<String>,
+
+    /// Path to a PEM bundle with extra root CA certificates, for gateways
+    /// served behind an internal CA
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+
+    /// Skip TLS certificate verification entirely. This exposes traffic
+    /// (including API keys) to interception — dev-only; prefer `ca_cert`.
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+fn default_no_proxy() -> Vec<String> {
+    vec!["localhost".into(), "127.0.0.1".into()]
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            no_proxy: default_no_proxy(),
+            ca_cert: None,
+            insecure: false,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Builder for a `reqwest` client with proxy and TLS settings applied.
+    /// An explicit `proxy` from config wins over the environment variables,
+    /// and the `no_proxy` list is honored for either source.
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder();
+        if let Some(ref path) = self.ca_cert {
+            match load_ca_bundle(path) {
+                Ok(certs) => {
+                    for cert in certs {
+                        builder = builder.add_root_certificate(cert);
+                    }
+                }
+                Err(e) => tracing::warn!("Ignoring CA bundle '{path}': {e}"),
+            }
+        }
+        if self.insecure {
+            tracing::warn!(
+                "TLS certificate verification disabled (http.insecure) — \
+                 traffic can be intercepted; use http.ca_cert instead where possible"
+            );
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        if let Some(url) = self.proxy.clone().or_else(env_proxy) {
+            match reqwest::Proxy::all(&url) {
+                Ok(proxy) => {
+                    let no_proxy = reqwest::NoProxy::from_string(&self.no_proxy.join(","));
+                    // Disable reqwest's own env-var handling so the
+                    // configured no_proxy list applies to the env proxy too
+                    builder = builder.no_proxy().proxy(proxy.no_proxy(no_proxy));
+                }
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid proxy URL '{url}': {e}");
+                }
+            }
+        }
+        builder
+    }
+}
+
+fn load_ca_bundle(path: &str) -> Result<Vec<reqwest::Certificate>, String> {
+    let pem = std::fs::read(path).map_err(|e| e.to_string())?;
+    reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| e.to_string())
+}
+
+fn env_proxy() -> Option<String> {
+    ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+}
+
 pub fn load_config(working_dir: Option<PathBuf>) -> Result<AppConfig, ConfigError> {
     let wd = working_dir.unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
@@ -224,7 +493,7 @@ pub fn load_config(working_dir: Option<PathBuf>) -> Result<AppConfig, ConfigErro
         if global_path.exists() {
             let content = std::fs::read_to_string(&global_path)
                 .map_err(|e| ConfigError::File(e.to_string()))?;
-            let file_config: AppConfig = serde_json::from_str(&content)
+            let file_config: AppConfigOverlay = serde_json::from_str(&content)
                 .map_err(|e| ConfigError::Invalid(e.to_string()))?;
             merge_config(&mut config, file_config);
         }
@@ -235,7 +504,7 @@ pub fn load_config(working_dir: Option<PathBuf>) -> Result<AppConfig, ConfigErro
     if local_path.exists() {
         let content = std::fs::read_to_string(&local_path)
             .map_err(|e| ConfigError::File(e.to_string()))?;
-        let file_config: AppConfig = serde_json::from_str(&content)
+        let file_config: AppConfigOverlay = serde_json::from_str(&content)
             .map_err(|e| ConfigError::Invalid(e.to_string()))?;
         merge_config(&mut config, file_config);
     }
@@ -249,48 +518,181 @@ pub fn load_config(working_dir: Option<PathBuf>) -> Result<AppConfig, ConfigErro
     Ok(config)
 }
 
-fn merge_config(base: &mut AppConfig, overlay: AppConfig) {
-    if overlay.api_key.is_some() {
-        base.api_key = overlay.api_key;
+/// Overlay parsed from a config file. Every field is optional so an
+/// explicit value always applies, even when it happens to equal the
+/// built-in default.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AppConfigOverlay {
+    pub api_key: Option<String>,
+    pub api_keys: Option<Vec<String>>,
+    pub provider_type: Option<ProviderType>,
+    pub openrouter_api_key: Option<String>,
+    pub anthropic_api_key: Option<String>,
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub agent: AgentConfigOverlay,
+    pub context_paths: Option<Vec<String>>,
+    pub include_files: Option<Vec<String>>,
+    pub debug: Option<bool>,
+    #[serde(default)]
+    pub tools: ToolsConfigOverlay,
+    #[serde(default)]
+    pub coderlm: CoderlmConfigOverlay,
+    #[serde(default)]
+    pub ui: UiConfigOverlay,
+    #[serde(default)]
+    pub http: HttpConfigOverlay,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AgentConfigOverlay {
+    pub coder_model: Option<ModelId>,
+    pub fast_model: Option<ModelId>,
+    pub reasoning_model: Option<ModelId>,
+    pub long_context_model: Option<ModelId>,
+    pub max_tokens: Option<u64>,
+    pub reasoning_effort: Option<String>,
+    pub auto_compact: Option<bool>,
+    pub auto_compact_threshold: Option<f64>,
+    pub compact_keep_recent: Option<usize>,
+    pub trim_keep_recent: Option<usize>,
+    pub summarize_tool_results: Option<bool>,
+    pub retry_attempts: Option<u32>,
+    pub retry_backoff_multiplier: Option<f64>,
+    pub stream_idle_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ToolsConfigOverlay {
+    pub concurrency: Option<std::collections::HashMap<String, usize>>,
+    pub default_timeout_secs: Option<u64>,
+    pub timeout_secs: Option<std::collections::HashMap<String, u64>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct CoderlmConfigOverlay {
+    pub server_url: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct UiConfigOverlay {
+    pub sidebar_width: Option<u16>,
+    pub spinner: Option<SpinnerStyle>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct HttpConfigOverlay {
+    pub proxy: Option<String>,
+    pub no_proxy: Option<Vec<String>>,
+    pub ca_cert: Option<String>,
+    pub insecure: Option<bool>,
+}
+
+pub(crate) fn merge_config(base: &mut AppConfig, overlay: AppConfigOverlay) {
+    if let Some(v) = overlay.api_key {
+        base.api_key = Some(v);
+    }
+    if let Some(v) = overlay.api_keys {
+        if !v.is_empty() {
+            base.api_keys = v;
+        }
+    }
+    if let Some(v) = overlay.anthropic_api_key {
+        base.anthropic_api_key = Some(v);
+    }
+    if let Some(v) = overlay.openrouter_api_key {
+        base.openrouter_api_key = Some(v);
+    }
+    if let Some(v) = overlay.provider_type {
+        base.provider_type = v;
+    }
+    if let Some(v) = overlay.base_url {
+        base.base_url = v;
+    }
+    if let Some(v) = overlay.agent.coder_model {
+        base.agent.coder_model = v;
+    }
+    if let Some(v) = overlay.agent.fast_model {
+        base.agent.fast_model = v;
+    }
+    if let Some(v) = overlay.agent.reasoning_model {
+        base.agent.reasoning_model = v;
+    }
+    if let Some(v) = overlay.agent.long_context_model {
+        base.agent.long_context_model = v;
+    }
+    if let Some(v) = overlay.agent.max_tokens {
+        base.agent.max_tokens = v;
+    }
+    if let Some(v) = overlay.agent.reasoning_effort {
+        base.agent.reasoning_effort = Some(v);
+    }
+    if let Some(v) = overlay.agent.auto_compact {
+        base.agent.auto_compact = v;
     }
-    if !overlay.api_keys.is_empty() {
-        base.api_keys = overlay.api_keys;
+    if let Some(v) = overlay.agent.auto_compact_threshold {
+        base.agent.auto_compact_threshold = v;
     }
-    if overlay.openrouter_api_key.is_some() {
-        base.openrouter_api_key = overlay.openrouter_api_key;
+    if let Some(v) = overlay.agent.compact_keep_recent {
+        base.agent.compact_keep_recent = v;
     }
-    if overlay.provider_type != ProviderType::default() {
-        base.provider_type = overlay.provider_type;
+    if let Some(v) = overlay.agent.trim_keep_recent {
+        base.agent.trim_keep_recent = v;
     }
-    if overlay.base_url != default_base_url() {
-        base.base_url = overlay.base_url;
+    if let Some(v) = overlay.agent.summarize_tool_results {
+        base.agent.summarize_tool_results = v;
     }
-    if overlay.agent.coder_model.0 != default_coder_model().0 {
-        base.agent.coder_model = overlay.agent.coder_model;
+    if let Some(v) = overlay.agent.retry_attempts {
+        base.agent.retry_attempts = v;
     }
-    if overlay.agent.fast_model.0 != default_fast_model().0 {
-        base.agent.fast_model = overlay.agent.fast_model;
+    if let Some(v) = overlay.agent.retry_backoff_multiplier {
+        base.agent.retry_backoff_multiplier = v;
     }
-    if overlay.agent.reasoning_model.0 != default_reasoning_model().0 {
-        base.agent.reasoning_model = overlay.agent.reasoning_model;
+    if let Some(v) = overlay.agent.stream_idle_timeout_secs {
+        base.agent.stream_idle_timeout_secs = v;
     }
-    if overlay.agent.long_context_model.0 != default_long_context_model().0 {
-        base.agent.long_context_model = overlay.agent.long_context_model;
+    if let Some(v) = overlay.context_paths {
+        base.context_paths = v;
     }
-    if overlay.agent.max_tokens != default_max_tokens() {
-        base.agent.max_tokens = overlay.agent.max_tokens;
+    if let Some(v) = overlay.include_files {
+        base.include_files = v;
     }
-    if overlay.agent.reasoning_effort.is_some() {
-        base.agent.reasoning_effort = overlay.agent.reasoning_effort;
+    if let Some(v) = overlay.debug {
+        base.debug = v;
     }
-    if overlay.debug {
-        base.debug = true;
+    if let Some(v) = overlay.tools.concurrency {
+        base.tools.concurrency = v;
     }
-    if overlay.coderlm.server_url != default_coderlm_url() {
-        base.coderlm.server_url = overlay.coderlm.server_url;
+    if let Some(v) = overlay.tools.default_timeout_secs {
+        base.tools.default_timeout_secs = v;
     }
-    if overlay.coderlm.timeout_secs != default_coderlm_timeout() {
-        base.coderlm.timeout_secs = overlay.coderlm.timeout_secs;
+    if let Some(v) = overlay.tools.timeout_secs {
+        base.tools.timeout_secs = v;
+    }
+    if let Some(v) = overlay.coderlm.server_url {
+        base.coderlm.server_url = v;
+    }
+    if let Some(v) = overlay.coderlm.timeout_secs {
+        base.coderlm.timeout_secs = v;
+    }
+    if let Some(v) = overlay.ui.sidebar_width {
+        base.ui.sidebar_width = v;
+    }
+    if let Some(v) = overlay.ui.spinner {
+        base.ui.spinner = v;
+    }
+    if let Some(v) = overlay.http.proxy {
+        base.http.proxy = Some(v);
+    }
+    if let Some(v) = overlay.http.no_proxy {
+        base.http.no_proxy = v;
+    }
+    if let Some(v) = overlay.http.ca_cert {
+        base.http.ca_cert = Some(v);
+    }
+    if let Some(v) = overlay.http.insecure {
+        base.http.insecure = v;
     }
 }
 
@@ -304,6 +706,16 @@ fn detect_api_key(config: &mut AppConfig) {
         }
     }
 
+    // Check for a dedicated Anthropic key (also used as an Atlas fallback
+    // below, so only take it for the native provider when set explicitly)
+    if config.anthropic_api_key.is_none() {
+        if let Ok(key) = std::env::var("ANTHROPIC_API_KEY") {
+            if !key.is_empty() {
+                config.anthropic_api_key = Some(key);
+            }
+        }
+    }
+
     // Check for multiple Atlas keys: ATLAS_API_KEYS=key1,key2,key3
     if config.api_keys.is_empty() {
         if let Ok(keys) = std::env::var("ATLAS_API_KEYS") {
@@ -381,7 +793,9 @@ impl AppConfig {
 
     /// Check if any provider has an API key configured
     pub fn has_any_api_key(&self) -> bool {
-        self.has_api_key() || self.openrouter_api_key.is_some()
+        self.has_api_key()
+            || self.openrouter_api_key.is_some()
+            || self.anthropic_api_key.is_some()
     }
 
     pub fn get_api_keys(&self) -> Vec<String> {
@@ -404,6 +818,13 @@ impl AppConfig {
                     vec![]
                 }
             }
+            ProviderType::Anthropic => {
+                if let Some(ref key) = self.anthropic_api_key {
+                    vec![key.clone()]
+                } else {
+                    vec![]
+                }
+            }
             ProviderType::AtlasCloud => self.get_api_keys(),
         }
     }
@@ -412,6 +833,7 @@ impl AppConfig {
     pub fn get_active_base_url(&self) -> String {
         match self.provider_type {
             ProviderType::OpenRouter => OPENROUTER_BASE_URL.to_string(),
+            ProviderType::Anthropic => ANTHROPIC_BASE_URL.to_string(),
             ProviderType::AtlasCloud => self.base_url.clone(),
         }
     }
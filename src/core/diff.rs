@@ -0,0 +1,178 @@
+//! Line-based unified diff, used by the file-mutating tools to report
+//! what actually changed and by the TUI diff viewer to render it.
+
+/// Lines of context kept around each change in a hunk
+const CONTEXT_LINES: usize = 3;
+
+/// Per-side line limit before diffing degrades to a summary. The LCS
+/// table is quadratic in the changed region, so unbounded inputs could
+/// stall the agent loop on a generated or minified file
+const MAX_DIFF_LINES: usize = 3_000;
+
+/// Unified diff of `old` vs `new`. New files diff cleanly as
+/// all-additions (pass an empty `old`). Binary or oversized content
+/// degrades to a single summary line instead of a hunk dump.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    if old.contains('\0') || new.contains('\0') {
+        return format!(
+            "Binary content changed ({} -> {} bytes)",
+            old.len(),
+            new.len()
+        );
+    }
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    // Trim the common prefix/suffix so the quadratic LCS only sees the
+    // changed region
+    let mut start = 0;
+    while start < old_lines.len()
+        && start < new_lines.len()
+        && old_lines[start] == new_lines[start]
+    {
+        start += 1;
+    }
+    let mut old_end = old_lines.len();
+    let mut new_end = new_lines.len();
+    while old_end > start && new_end > start && old_lines[old_end - 1] == new_lines[new_end - 1] {
+        old_end -= 1;
+        new_end -= 1;
+    }
+
+    if old_end == start && new_end == start {
+        return String::new();
+    }
+
+    if old_end - start > MAX_DIFF_LINES || new_end - start > MAX_DIFF_LINES {
+        return format!(
+            "File changed ({} -> {} lines; too large to diff)",
+            old_lines.len(),
+            new_lines.len()
+        );
+    }
+
+    // Edit script over the changed region, as (old_index, new_index)
+    // pairs of matching lines
+    let matches = lcs_matches(&old_lines[start..old_end], &new_lines[start..new_end]);
+
+    // Expand back to full-file ops: Equal / Delete / Insert per line
+    #[derive(PartialEq)]
+    enum Op {
+        Equal,
+        Delete,
+        Insert,
+    }
+    let mut ops: Vec<(Op, usize, usize)> = Vec::new(); // (op, old_idx, new_idx)
+    for i in 0..start {
+        ops.push((Op::Equal, i, i));
+    }
+    let (mut oi, mut ni) = (start, start);
+    for &(mo, mn) in &matches {
+        let (mo, mn) = (mo + start, mn + start);
+        while oi < mo {
+            ops.push((Op::Delete, oi, ni));
+            oi += 1;
+        }
+        while ni < mn {
+            ops.push((Op::Insert, oi, ni));
+            ni += 1;
+        }
+        ops.push((Op::Equal, oi, ni));
+        oi += 1;
+        ni += 1;
+    }
+    while oi < old_end {
+        ops.push((Op::Delete, oi, ni));
+        oi += 1;
+    }
+    while ni < new_end {
+        ops.push((Op::Insert, oi, ni));
+        ni += 1;
+    }
+    for (o, n) in (old_end..old_lines.len()).zip(new_end..new_lines.len()) {
+        ops.push((Op::Equal, o, n));
+    }
+
+    // Group changes into hunks with `CONTEXT_LINES` of surrounding context
+    let mut out = String::new();
+    let mut i = 0;
+    while i < ops.len() {
+        if ops[i].0 == Op::Equal {
+            i += 1;
+            continue;
+        }
+
+        // Extend the hunk while changes stay within 2*context of each other
+        let hunk_start = i.saturating_sub(CONTEXT_LINES);
+        let mut hunk_end = i;
+        let mut last_change = i;
+        while hunk_end < ops.len() {
+            if ops[hunk_end].0 != Op::Equal {
+                last_change = hunk_end;
+            } else if hunk_end - last_change > CONTEXT_LINES * 2 {
+                break;
+            }
+            hunk_end += 1;
+        }
+        let hunk_end = (last_change + CONTEXT_LINES + 1).min(ops.len());
+
+        let old_start = ops[hunk_start].1;
+        let new_start = ops[hunk_start].2;
+        let old_count = ops[hunk_start..hunk_end]
+            .iter()
+            .filter(|(op, _, _)| *op != Op::Insert)
+            .count();
+        let new_count = ops[hunk_start..hunk_end]
+            .iter()
+            .filter(|(op, _, _)| *op != Op::Delete)
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_count,
+            new_start + 1,
+            new_count
+        ));
+        for (op, o, n) in &ops[hunk_start..hunk_end] {
+            match op {
+                Op::Equal => out.push_str(&format!(" {}\n", old_lines[*o])),
+                Op::Delete => out.push_str(&format!("-{}\n", old_lines[*o])),
+                Op::Insert => out.push_str(&format!("+{}\n", new_lines[*n])),
+            }
+        }
+        i = hunk_end;
+    }
+
+    out
+}
+
+/// Longest common subsequence of the two slices, as matching index pairs
+fn lcs_matches(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let mut table = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i * (m + 1) + j] = if old[i] == new[j] {
+                table[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                table[(i + 1) * (m + 1) + j].max(table[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * (m + 1) + j] >= table[i * (m + 1) + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
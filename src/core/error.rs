@@ -61,6 +61,12 @@ pub enum ToolError {
 
     #[error("Permission denied for tool '{tool}' action '{action}'")]
     PermissionDenied { tool: String, action: String },
+
+    #[error("Cancelled")]
+    Cancelled,
+
+    #[error("A tool named '{0}' is already registered")]
+    DuplicateName(String),
 }
 
 #[derive(Error, Debug)]
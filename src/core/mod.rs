@@ -1,4 +1,5 @@
 pub mod config;
+pub mod diff;
 pub mod error;
 pub mod message;
 pub mod model;
@@ -77,10 +77,25 @@ pub struct Model {
 }
 
 impl Model {
-    pub fn calculate_cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
-        let input_cost = (input_tokens as f64 / 1_000_000.0) * self.pricing.cost_per_1m_input;
+    /// Cost in dollars. `cached_input_tokens` is the cache-read subset of
+    /// `input_tokens`; it is billed at the cached rate when the model has
+    /// one, and at the full input price otherwise
+    pub fn calculate_cost(
+        &self,
+        input_tokens: u64,
+        cached_input_tokens: u64,
+        output_tokens: u64,
+    ) -> f64 {
+        let cached = cached_input_tokens.min(input_tokens);
+        let cached_rate = self
+            .pricing
+            .cost_per_1m_input_cached
+            .unwrap_or(self.pricing.cost_per_1m_input);
+        let input_cost =
+            ((input_tokens - cached) as f64 / 1_000_000.0) * self.pricing.cost_per_1m_input;
+        let cached_cost = (cached as f64 / 1_000_000.0) * cached_rate;
         let output_cost = (output_tokens as f64 / 1_000_000.0) * self.pricing.cost_per_1m_output;
-        input_cost + output_cost
+        input_cost + cached_cost + output_cost
     }
 }
 
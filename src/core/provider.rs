@@ -25,6 +25,69 @@ pub struct ProviderResponse {
     pub usage: TokenUsage,
 }
 
+/// OpenAI-style `tool_choice` controlling whether the model may, must,
+/// or must not call tools on the next request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolChoice {
+    /// Model decides freely (the API default)
+    Auto,
+    /// No tool calls; answer directly
+    None,
+    /// Must call at least one tool
+    Required,
+    /// Must call the named tool
+    Tool(String),
+}
+
+impl ToolChoice {
+    /// Serialize to the request-body value the OpenAI API expects
+    pub fn as_body_value(&self) -> serde_json::Value {
+        match self {
+            ToolChoice::Auto => serde_json::json!("auto"),
+            ToolChoice::None => serde_json::json!("none"),
+            ToolChoice::Required => serde_json::json!("required"),
+            ToolChoice::Tool(name) => serde_json::json!({
+                "type": "function",
+                "function": { "name": name },
+            }),
+        }
+    }
+}
+
+/// Snapshot of recent rate-limit pressure on a provider.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitStatus {
+    /// How many of the last `window` requests were rate limited
+    pub limited_recent: usize,
+    /// Size of the sliding window `limited_recent` is counted over
+    pub window: usize,
+    /// Milliseconds until the current backoff expires (0 when idle)
+    pub backoff_remaining_ms: u64,
+}
+
+impl RateLimitStatus {
+    /// True when the majority of recent requests were rate limited,
+    /// suggesting a quota problem worth warning the user about
+    pub fn under_pressure(&self) -> bool {
+        self.window > 0 && self.limited_recent * 2 > self.window
+    }
+}
+
+/// Outcome of a provider connectivity check.
+#[derive(Debug, Clone)]
+pub struct PingResult {
+    pub auth_ok: bool,
+    pub model_available: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+impl PingResult {
+    pub fn is_healthy(&self) -> bool {
+        self.auth_ok && self.model_available && self.error.is_none()
+    }
+}
+
 pub type ProviderEventStream =
     Pin<Box<dyn futures_core::Stream<Item = ProviderEvent> + Send>>;
 
@@ -37,6 +100,22 @@ pub trait Provider: Send + Sync {
         tools: &[ToolDefinition],
     ) -> Result<ProviderResponse, ProviderError>;
 
+    /// Like `send_messages`, but with an OpenAI-style `response_format`
+    /// (e.g. `{"type": "json_schema", ...}`) for structured outputs.
+    ///
+    /// The default implementation ignores the format; providers that
+    /// support it should inject it into the request body. Callers must be
+    /// prepared to fall back to prompt-based JSON instructions.
+    async fn send_messages_with_format(
+        &self,
+        messages: &[Message],
+        system_prompt: &str,
+        tools: &[ToolDefinition],
+        _response_format: Option<&serde_json::Value>,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.send_messages(messages, system_prompt, tools).await
+    }
+
     async fn stream_response(
         &self,
         messages: &[Message],
@@ -45,4 +124,75 @@ pub trait Provider: Send + Sync {
     ) -> Result<ProviderEventStream, ProviderError>;
 
     fn model(&self) -> &Model;
+
+    /// Set the sampling temperature for subsequent requests (`None`
+    /// restores the provider default). Providers without runtime
+    /// parameters ignore it.
+    fn set_temperature(&self, _temperature: Option<f64>) {}
+
+    fn temperature(&self) -> Option<f64> {
+        None
+    }
+
+    /// Set the `tool_choice` for subsequent requests (`None` restores the
+    /// API default of `auto`). Providers without tool support ignore it.
+    fn set_tool_choice(&self, _choice: Option<ToolChoice>) {}
+
+    fn tool_choice(&self) -> Option<ToolChoice> {
+        None
+    }
+
+    /// Recent rate-limit pressure, for proactive UI warnings. Providers
+    /// that don't track it report no pressure.
+    fn rate_limit_status(&self) -> RateLimitStatus {
+        RateLimitStatus::default()
+    }
+
+    /// Override `max_tokens` for subsequent requests (`None` restores the
+    /// configured value).
+    fn set_max_tokens_override(&self, _max_tokens: Option<u64>) {}
+
+    fn max_tokens_override(&self) -> Option<u64> {
+        None
+    }
+
+    /// Check connectivity by sending a minimal completion request.
+    ///
+    /// Verifies the base URL, API key, and model in one round trip and
+    /// returns a structured diagnosis instead of failing cryptically on
+    /// the first real message.
+    async fn ping(&self) -> PingResult {
+        let start = std::time::Instant::now();
+        let messages = [Message::new_user("ping".into(), "ping".into())];
+        let result = self
+            .send_messages(&messages, "Reply with the single word: pong", &[])
+            .await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(_) => PingResult {
+                auth_ok: true,
+                model_available: true,
+                latency_ms,
+                error: None,
+            },
+            Err(e) => {
+                let (auth_ok, model_available) = match &e {
+                    ProviderError::MissingApiKey(_) => (false, true),
+                    ProviderError::Api { status, .. } if *status == 401 || *status == 403 => {
+                        (false, true)
+                    }
+                    ProviderError::Api { status, .. } if *status == 404 => (true, false),
+                    ProviderError::UnsupportedModel(_) => (true, false),
+                    _ => (false, false),
+                };
+                PingResult {
+                    auth_ok,
+                    model_available,
+                    latency_ms,
+                    error: Some(e.to_string()),
+                }
+            }
+        }
+    }
 }
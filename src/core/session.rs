@@ -9,6 +9,13 @@ pub struct Session {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub cost: f64,
+    /// Most recent model used in this session, for auditing spend
+    pub model_id: Option<String>,
+    /// Provider the model was routed through (e.g. "atlas_cloud")
+    pub provider: Option<String>,
+    /// Files changed by tools over the session's lifetime, for the sidebar
+    #[serde(default)]
+    pub changed_files: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -23,6 +30,9 @@ impl Session {
             prompt_tokens: 0,
             completion_tokens: 0,
             cost: 0.0,
+            model_id: None,
+            provider: None,
+            changed_files: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -120,11 +120,25 @@ fn test_model_registry() {
 fn test_model_cost_calculation() {
     let model = get_model(&ModelId("zai-org/glm-5".into())).unwrap();
     // 1000 input tokens, 500 output tokens
-    let cost = model.calculate_cost(1000, 500);
+    let cost = model.calculate_cost(1000, 0, 500);
     // (1000/1M * 0.80) + (500/1M * 2.56) = 0.0008 + 0.00128 = 0.00208
     assert!((cost - 0.00208).abs() < 0.0001);
 }
 
+#[test]
+fn test_model_cost_with_cached_input() {
+    let model = get_model(&ModelId("zai-org/glm-5".into())).unwrap();
+    // 400 of the 1000 input tokens were cache reads at the $0.16/1M rate
+    let cost = model.calculate_cost(1000, 400, 500);
+    // (600/1M * 0.80) + (400/1M * 0.16) + (500/1M * 2.56)
+    let expected = 0.00048 + 0.000064 + 0.00128;
+    assert!((cost - expected).abs() < 1e-9);
+
+    // Cached count can't exceed total input
+    let clamped = model.calculate_cost(100, 1000, 0);
+    assert!((clamped - 100.0 / 1_000_000.0 * 0.16).abs() < 1e-9);
+}
+
 #[test]
 fn test_all_six_models_exist() {
     let ids = [
@@ -155,6 +169,76 @@ fn test_config_defaults() {
     assert_eq!(config.base_url, "https://api.atlascloud.ai");
     assert_eq!(config.agent.coder_model.0, "zai-org/glm-5");
     assert_eq!(config.agent.max_tokens, 16_384);
+    assert!(!config.agent.auto_compact);
+    assert_eq!(config.agent.auto_compact_threshold, 0.8);
+    assert_eq!(config.ui.sidebar_width, 24);
+    assert_eq!(config.tools.default_timeout_secs, 300);
+    assert_eq!(config.agent.compact_keep_recent, 4);
+    assert_eq!(config.agent.trim_keep_recent, 4);
+    assert!(!config.agent.summarize_tool_results);
+    assert_eq!(config.agent.retry_attempts, 3);
+    assert_eq!(config.agent.retry_backoff_multiplier, 1.0);
+    assert!(config.http.proxy.is_none());
+    // CodeRLM runs on localhost and must bypass any configured proxy
+    assert!(config.http.no_proxy.contains(&"localhost".to_string()));
+    assert!(config.http.no_proxy.contains(&"127.0.0.1".to_string()));
+    assert!(config.http.ca_cert.is_none());
+    assert!(!config.http.insecure);
+}
+
+#[test]
+fn test_tool_timeout_lookup() {
+    let mut config = crate::core::config::ToolsConfig::default();
+    config.timeout_secs.insert("coderlm".into(), 30);
+    assert_eq!(
+        config.timeout_for("coderlm"),
+        Some(std::time::Duration::from_secs(30))
+    );
+    assert_eq!(
+        config.timeout_for("bash"),
+        Some(std::time::Duration::from_secs(300))
+    );
+
+    // 0 disables the timeout
+    config.default_timeout_secs = 0;
+    assert_eq!(config.timeout_for("bash"), None);
+}
+
+#[test]
+fn test_merge_config_explicit_default_wins() {
+    use crate::core::config::{merge_config, AppConfig, AppConfigOverlay};
+
+    let mut base = AppConfig::default();
+    base.base_url = "https://gateway.internal".into();
+    base.agent.coder_model = ModelId("other/model".into());
+
+    // A local config that explicitly pins the built-in defaults must still
+    // override a different global value
+    let overlay: AppConfigOverlay = serde_json::from_str(
+        r#"{"base_url": "https://api.atlascloud.ai", "agent": {"coder_model": "zai-org/glm-5"}}"#,
+    )
+    .unwrap();
+    merge_config(&mut base, overlay);
+
+    assert_eq!(base.base_url, "https://api.atlascloud.ai");
+    assert_eq!(base.agent.coder_model.0, "zai-org/glm-5");
+}
+
+#[test]
+fn test_merge_config_unset_fields_keep_base() {
+    use crate::core::config::{merge_config, AppConfig, AppConfigOverlay};
+
+    let mut base = AppConfig::default();
+    base.api_key = Some("base-key".into());
+    base.debug = true;
+
+    let overlay: AppConfigOverlay = serde_json::from_str(r#"{"max_tokens": 1}"#).unwrap();
+    merge_config(&mut base, overlay);
+
+    assert_eq!(base.api_key.as_deref(), Some("base-key"));
+    assert!(base.debug);
+    // Unknown top-level key is ignored; agent.max_tokens untouched
+    assert_eq!(base.agent.max_tokens, 16_384);
 }
 
 #[test]
@@ -169,6 +253,44 @@ fn test_config_has_api_key() {
     assert!(!config.has_api_key());
 }
 
+#[test]
+fn test_ping_result_health() {
+    let healthy = crate::core::provider::PingResult {
+        auth_ok: true,
+        model_available: true,
+        latency_ms: 120,
+        error: None,
+    };
+    assert!(healthy.is_healthy());
+
+    let bad_auth = crate::core::provider::PingResult {
+        auth_ok: false,
+        model_available: true,
+        latency_ms: 80,
+        error: Some("API error (401): invalid key".into()),
+    };
+    assert!(!bad_auth.is_healthy());
+}
+
+#[test]
+fn test_tool_choice_body_values() {
+    use crate::core::provider::ToolChoice;
+
+    assert_eq!(ToolChoice::Auto.as_body_value(), serde_json::json!("auto"));
+    assert_eq!(ToolChoice::None.as_body_value(), serde_json::json!("none"));
+    assert_eq!(
+        ToolChoice::Required.as_body_value(),
+        serde_json::json!("required")
+    );
+    assert_eq!(
+        ToolChoice::Tool("bash".into()).as_body_value(),
+        serde_json::json!({
+            "type": "function",
+            "function": { "name": "bash" },
+        })
+    );
+}
+
 #[test]
 fn test_message_role_serialization() {
     let role = MessageRole::Assistant;
@@ -188,3 +310,42 @@ fn test_finish_reason_serialization() {
     let deserialized: FinishReason = serde_json::from_str("\"end_turn\"").unwrap();
     assert_eq!(deserialized, FinishReason::EndTurn);
 }
+
+#[test]
+fn test_unified_diff_edit() {
+    let old = "fn main() {\n    println!(\"a\");\n    println!(\"b\");\n    println!(\"c\");\n}\n";
+    let new = "fn main() {\n    println!(\"a\");\n    println!(\"B\");\n    println!(\"c\");\n}\n";
+    let diff = super::diff::unified_diff(old, new);
+    assert!(diff.starts_with("@@ -1,5 +1,5 @@\n"), "got:\n{diff}");
+    assert!(diff.contains("-    println!(\"b\");\n"));
+    assert!(diff.contains("+    println!(\"B\");\n"));
+    // Unchanged lines appear as context
+    assert!(diff.contains(" fn main() {\n"));
+}
+
+#[test]
+fn test_unified_diff_new_file_is_all_additions() {
+    let diff = super::diff::unified_diff("", "line one\nline two\n");
+    assert!(diff.contains("+line one\n"));
+    assert!(diff.contains("+line two\n"));
+    assert!(!diff.lines().any(|l| l.starts_with('-')));
+}
+
+#[test]
+fn test_unified_diff_degrades_for_binary_and_identical() {
+    assert_eq!(super::diff::unified_diff("same\n", "same\n"), "");
+
+    let diff = super::diff::unified_diff("text", "bin\0ary");
+    assert!(diff.starts_with("Binary content changed"), "got: {diff}");
+}
+
+#[test]
+fn test_unified_diff_separate_hunks() {
+    // Two changes far apart land in two hunks rather than one giant one
+    let old: String = (1..=30).map(|i| format!("line {i}\n")).collect();
+    let new = old.replace("line 2\n", "LINE 2\n").replace("line 28\n", "LINE 28\n");
+    let diff = super::diff::unified_diff(&old, &new);
+    assert_eq!(diff.lines().filter(|l| l.starts_with("@@")).count(), 2, "got:\n{diff}");
+    assert!(diff.contains("+LINE 2"));
+    assert!(diff.contains("+LINE 28"));
+}
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
@@ -20,7 +20,10 @@ pub struct ParamSchema {
 pub struct ToolDefinition {
     pub name: String,
     pub description: String,
-    pub parameters: HashMap<String, ParamSchema>,
+    /// Keyed by a `BTreeMap` so the serialized parameter order is stable
+    /// across runs — keeps the tools payload byte-identical for prompt
+    /// caching and request snapshots
+    pub parameters: BTreeMap<String, ParamSchema>,
     pub required: Vec<String>,
 }
 
@@ -37,6 +40,10 @@ pub struct ToolResult {
     pub is_error: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
+    /// Files this call created, modified or deleted, reported by the tool
+    /// itself so consumers don't have to scrape the result text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affected_paths: Vec<String>,
 }
 
 impl ToolResult {
@@ -45,6 +52,7 @@ impl ToolResult {
             content,
             is_error: false,
             metadata: None,
+            affected_paths: Vec::new(),
         }
     }
 
@@ -53,8 +61,14 @@ impl ToolResult {
             content: message,
             is_error: true,
             metadata: None,
+            affected_paths: Vec::new(),
         }
     }
+
+    pub fn with_affected_paths(mut self, paths: Vec<String>) -> Self {
+        self.affected_paths = paths;
+        self
+    }
 }
 
 pub struct ToolContext {
@@ -68,9 +82,22 @@ pub struct ToolContext {
 pub trait Tool: Send + Sync {
     fn definition(&self) -> ToolDefinition;
 
+    /// Execute the tool call.
+    ///
+    /// Implementations that spawn subprocesses or make network requests MUST
+    /// watch `ctx.cancel_token` (typically with `tokio::select!`) and abort
+    /// their work promptly, returning [`ToolError::Cancelled`]. The agent loop
+    /// drops the `run` future on cancellation as a backstop, but any side
+    /// effects that outlive the future (e.g. a spawned process) are the
+    /// tool's responsibility to clean up.
     async fn run(
         &self,
         call: &ToolCall,
         ctx: &ToolContext,
     ) -> Result<ToolResult, ToolError>;
+
+    /// Called after another tool successfully mutates the given files, so
+    /// tools holding derived state (e.g. a code index) can refresh it.
+    /// Best-effort; failures must not surface to the conversation.
+    async fn on_files_changed(&self, _paths: &[String], _ctx: &ToolContext) {}
 }
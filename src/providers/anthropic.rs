@@ -0,0 +1,535 @@
+use async_trait::async_trait;
+use crate::core::error::ProviderError;
+use crate::core::message::*;
+use crate::core::model::Model;
+use crate::core::provider::*;
+use crate::core::tool::ToolDefinition;
+use reqwest::Client;
+use std::sync::Arc;
+
+use super::openai::{compute_backoff, MAX_RETRIES};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Native Anthropic Messages API provider.
+///
+/// Unlike the OpenAI-compatible path, the Messages API takes `system` as a
+/// top-level field, represents content as typed blocks, and pairs
+/// `tool_use` blocks with `tool_result` blocks in user messages.
+/// Cache-control and thinking blocks are not sent yet.
+pub struct AnthropicProvider {
+    client: Client,
+    api_key: String,
+    model: Model,
+    base_url: String,
+    max_tokens: u64,
+    temperature: Arc<std::sync::RwLock<Option<f64>>>,
+    max_tokens_override: Arc<std::sync::RwLock<Option<u64>>>,
+    tool_choice: Arc<std::sync::RwLock<Option<ToolChoice>>>,
+}
+
+impl AnthropicProvider {
+    pub fn new(
+        api_key: String,
+        model: Model,
+        base_url: String,
+        max_tokens: u64,
+        http: &crate::core::config::HttpConfig,
+    ) -> Self {
+        Self {
+            client: http.client_builder().build().unwrap_or_default(),
+            api_key,
+            model,
+            base_url,
+            max_tokens,
+            temperature: Arc::new(std::sync::RwLock::new(None)),
+            max_tokens_override: Arc::new(std::sync::RwLock::new(None)),
+            tool_choice: Arc::new(std::sync::RwLock::new(None)),
+        }
+    }
+
+    /// Effective max_tokens for the next request
+    fn effective_max_tokens(&self) -> u64 {
+        self.max_tokens_override
+            .read()
+            .unwrap()
+            .unwrap_or(self.max_tokens)
+    }
+
+    fn convert_messages(&self, messages: &[Message]) -> Vec<serde_json::Value> {
+        let mut result = Vec::new();
+
+        for msg in messages {
+            match msg.role {
+                // System content goes in the top-level `system` field
+                MessageRole::System => continue,
+                MessageRole::User => {
+                    let text = msg.text_content();
+                    if !text.is_empty() {
+                        result.push(serde_json::json!({
+                            "role": "user",
+                            "content": [{"type": "text", "text": text}],
+                        }));
+                    }
+                }
+                MessageRole::Assistant => {
+                    let mut blocks = Vec::new();
+                    for part in &msg.parts {
+                        match part {
+                            ContentPart::Text { text } => {
+                                blocks.push(serde_json::json!({
+                                    "type": "text",
+                                    "text": text,
+                                }));
+                            }
+                            ContentPart::ToolCall { id, name, input } => {
+                                let input_val: serde_json::Value =
+                                    serde_json::from_str(input).unwrap_or(serde_json::json!({}));
+                                blocks.push(serde_json::json!({
+                                    "type": "tool_use",
+                                    "id": id,
+                                    "name": name,
+                                    "input": input_val,
+                                }));
+                            }
+                            _ => {}
+                        }
+                    }
+                    if !blocks.is_empty() {
+                        result.push(serde_json::json!({
+                            "role": "assistant",
+                            "content": blocks,
+                        }));
+                    }
+                }
+                // Tool results are user-role messages with tool_result blocks
+                MessageRole::Tool => {
+                    let mut blocks = Vec::new();
+                    for part in &msg.parts {
+                        if let ContentPart::ToolResult {
+                            tool_call_id,
+                            content,
+                            is_error,
+                        } = part
+                        {
+                            blocks.push(serde_json::json!({
+                                "type": "tool_result",
+                                "tool_use_id": tool_call_id,
+                                "content": content,
+                                "is_error": is_error,
+                            }));
+                        }
+                    }
+                    if !blocks.is_empty() {
+                        result.push(serde_json::json!({
+                            "role": "user",
+                            "content": blocks,
+                        }));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    fn convert_tools(&self, tools: &[ToolDefinition]) -> Vec<serde_json::Value> {
+        tools
+            .iter()
+            .map(|t| {
+                let properties: serde_json::Map<String, serde_json::Value> = t
+                    .parameters
+                    .iter()
+                    .map(|(k, v)| {
+                        let mut schema = serde_json::Map::new();
+                        schema.insert(
+                            "type".into(),
+                            serde_json::Value::String(v.param_type.clone()),
+                        );
+                        schema.insert(
+                            "description".into(),
+                            serde_json::Value::String(v.description.clone()),
+                        );
+                        if let Some(enums) = &v.enum_values {
+                            schema.insert("enum".into(), serde_json::json!(enums));
+                        }
+                        (k.clone(), serde_json::Value::Object(schema))
+                    })
+                    .collect();
+
+                serde_json::json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "input_schema": {
+                        "type": "object",
+                        "properties": properties,
+                        "required": t.required,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn build_body(
+        &self,
+        messages: &[Message],
+        system_prompt: &str,
+        tools: &[ToolDefinition],
+        stream: bool,
+    ) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "model": self.model.id.0,
+            "max_tokens": self.effective_max_tokens(),
+            "system": system_prompt,
+            "messages": self.convert_messages(messages),
+        });
+
+        if stream {
+            body["stream"] = serde_json::json!(true);
+        }
+
+        if !tools.is_empty() {
+            body["tools"] = serde_json::json!(self.convert_tools(tools));
+            if let Some(choice) = self.tool_choice.read().unwrap().as_ref() {
+                body["tool_choice"] = tool_choice_body(choice);
+            }
+        }
+
+        if let Some(temp) = *self.temperature.read().unwrap() {
+            body["temperature"] = serde_json::json!(temp);
+        }
+
+        body
+    }
+
+    async fn post_with_retries(
+        &self,
+        body: &serde_json::Value,
+    ) -> Result<reqwest::Response, ProviderError> {
+        let mut last_err = ProviderError::Http("no attempts made".into());
+
+        for attempt in 0..MAX_RETRIES {
+            if attempt > 0 {
+                let backoff = compute_backoff(attempt, None);
+                eprintln!(
+                    "\x1b[33m[retry {}/{}] Rate limited, waiting {:.1}s...\x1b[0m",
+                    attempt, MAX_RETRIES - 1, backoff as f64 / 1000.0
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
+            }
+
+            let resp = match self
+                .client
+                .post(format!("{}/v1/messages", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .header("Content-Type", "application/json")
+                .json(body)
+                .send()
+                .await
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    last_err = ProviderError::Http(e.to_string());
+                    continue;
+                }
+            };
+
+            let status = resp.status().as_u16();
+            if resp.status().is_success() {
+                return Ok(resp);
+            }
+
+            let retry_after = resp
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(super::openai::parse_retry_after);
+
+            let text = resp.text().await.unwrap_or_default();
+            // 529 is Anthropic's "overloaded" status
+            if status == 429 || status == 529 || status == 503 {
+                let wait = retry_after.unwrap_or(compute_backoff(attempt + 1, None));
+                last_err = ProviderError::RateLimited {
+                    retry_after_ms: wait,
+                };
+                continue;
+            }
+            return Err(ProviderError::Api {
+                status,
+                message: text,
+            });
+        }
+
+        Err(last_err)
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    async fn send_messages(
+        &self,
+        messages: &[Message],
+        system_prompt: &str,
+        tools: &[ToolDefinition],
+    ) -> Result<ProviderResponse, ProviderError> {
+        let body = self.build_body(messages, system_prompt, tools, false);
+        let resp = self.post_with_retries(&body).await?;
+        let api_resp: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| ProviderError::Http(e.to_string()))?;
+        parse_anthropic_response(api_resp)
+    }
+
+    async fn stream_response(
+        &self,
+        messages: &[Message],
+        system_prompt: &str,
+        tools: &[ToolDefinition],
+    ) -> Result<ProviderEventStream, ProviderError> {
+        let body = self.build_body(messages, system_prompt, tools, true);
+        let resp = self.post_with_retries(&body).await?;
+
+        let byte_stream = resp.bytes_stream();
+
+        let stream = async_stream::stream! {
+            use tokio_stream::StreamExt;
+
+            let mut byte_stream = Box::pin(byte_stream);
+            let mut buffer = String::new();
+            // Type of the currently open content block, to pair the right
+            // stop event with content_block_stop
+            let mut current_block: Option<String> = None;
+            let mut usage = TokenUsage::default();
+            let mut finish_reason = FinishReason::EndTurn;
+
+            'outer: while let Some(chunk) = byte_stream.next().await {
+                let chunk = match chunk {
+                    Ok(c) => c,
+                    Err(e) => {
+                        yield ProviderEvent::Error {
+                            error: ProviderError::Stream(e.to_string()),
+                        };
+                        break;
+                    }
+                };
+
+                buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+                while let Some(line_end) = buffer.find('\n') {
+                    let line = buffer[..line_end].trim().to_string();
+                    buffer = buffer[line_end + 1..].to_string();
+
+                    let data = match line.strip_prefix("data: ") {
+                        Some(d) => d.trim(),
+                        None => continue,
+                    };
+
+                    let json: serde_json::Value = match serde_json::from_str(data) {
+                        Ok(j) => j,
+                        Err(_) => continue,
+                    };
+
+                    match json["type"].as_str() {
+                        Some("message_start") => {
+                            usage.input_tokens = json["message"]["usage"]["input_tokens"]
+                                .as_u64()
+                                .unwrap_or(0);
+                            usage.cache_creation_tokens = json["message"]["usage"]
+                                ["cache_creation_input_tokens"]
+                                .as_u64()
+                                .unwrap_or(0);
+                            usage.cache_read_tokens = json["message"]["usage"]
+                                ["cache_read_input_tokens"]
+                                .as_u64()
+                                .unwrap_or(0);
+                        }
+                        Some("content_block_start") => {
+                            let block = &json["content_block"];
+                            match block["type"].as_str() {
+                                Some("text") => {
+                                    current_block = Some("text".into());
+                                    yield ProviderEvent::ContentStart;
+                                }
+                                Some("tool_use") => {
+                                    current_block = Some("tool_use".into());
+                                    yield ProviderEvent::ToolUseStart {
+                                        id: block["id"].as_str().unwrap_or("").to_string(),
+                                        name: block["name"].as_str().unwrap_or("").to_string(),
+                                    };
+                                }
+                                other => {
+                                    current_block = other.map(String::from);
+                                }
+                            }
+                        }
+                        Some("content_block_delta") => {
+                            let delta = &json["delta"];
+                            match delta["type"].as_str() {
+                                Some("text_delta") => {
+                                    if let Some(text) = delta["text"].as_str() {
+                                        yield ProviderEvent::ContentDelta {
+                                            text: text.to_string(),
+                                        };
+                                    }
+                                }
+                                Some("input_json_delta") => {
+                                    if let Some(partial) = delta["partial_json"].as_str() {
+                                        if !partial.is_empty() {
+                                            yield ProviderEvent::ToolUseDelta {
+                                                input_json_chunk: partial.to_string(),
+                                            };
+                                        }
+                                    }
+                                }
+                                Some("thinking_delta") => {
+                                    if let Some(text) = delta["thinking"].as_str() {
+                                        yield ProviderEvent::ThinkingDelta {
+                                            text: text.to_string(),
+                                        };
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        Some("content_block_stop") => {
+                            match current_block.take().as_deref() {
+                                Some("text") => yield ProviderEvent::ContentStop,
+                                Some("tool_use") => yield ProviderEvent::ToolUseStop,
+                                _ => {}
+                            }
+                        }
+                        Some("message_delta") => {
+                            if let Some(reason) = json["delta"]["stop_reason"].as_str() {
+                                finish_reason = match reason {
+                                    "end_turn" => FinishReason::EndTurn,
+                                    "max_tokens" => FinishReason::MaxTokens,
+                                    "tool_use" => FinishReason::ToolUse,
+                                    _ => FinishReason::EndTurn,
+                                };
+                            }
+                            if let Some(out) = json["usage"]["output_tokens"].as_u64() {
+                                usage.output_tokens = out;
+                            }
+                        }
+                        Some("message_stop") => {
+                            yield ProviderEvent::Complete {
+                                finish_reason,
+                                usage: usage.clone(),
+                            };
+                        }
+                        Some("error") => {
+                            yield ProviderEvent::Error {
+                                error: ProviderError::Stream(
+                                    json["error"]["message"]
+                                        .as_str()
+                                        .unwrap_or("unknown stream error")
+                                        .to_string(),
+                                ),
+                            };
+                            break 'outer;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    fn model(&self) -> &Model {
+        &self.model
+    }
+
+    fn set_tool_choice(&self, choice: Option<ToolChoice>) {
+        *self.tool_choice.write().unwrap() = choice;
+    }
+
+    fn tool_choice(&self) -> Option<ToolChoice> {
+        self.tool_choice.read().unwrap().clone()
+    }
+
+    fn set_temperature(&self, temperature: Option<f64>) {
+        *self.temperature.write().unwrap() = temperature;
+    }
+
+    fn temperature(&self) -> Option<f64> {
+        *self.temperature.read().unwrap()
+    }
+
+    fn set_max_tokens_override(&self, max_tokens: Option<u64>) {
+        *self.max_tokens_override.write().unwrap() = max_tokens;
+    }
+
+    fn max_tokens_override(&self) -> Option<u64> {
+        *self.max_tokens_override.read().unwrap()
+    }
+}
+
+/// Map [`ToolChoice`] to the Messages API `tool_choice` shape
+fn tool_choice_body(choice: &ToolChoice) -> serde_json::Value {
+    match choice {
+        ToolChoice::Auto => serde_json::json!({"type": "auto"}),
+        ToolChoice::None => serde_json::json!({"type": "none"}),
+        ToolChoice::Required => serde_json::json!({"type": "any"}),
+        ToolChoice::Tool(name) => serde_json::json!({"type": "tool", "name": name}),
+    }
+}
+
+fn parse_anthropic_response(
+    json: serde_json::Value,
+) -> Result<ProviderResponse, ProviderError> {
+    let blocks = json["content"].as_array().ok_or_else(|| {
+        let msg = json["error"]["message"]
+            .as_str()
+            .unwrap_or("No content in response");
+        ProviderError::Stream(msg.to_string())
+    })?;
+
+    let mut content = Vec::new();
+    for block in blocks {
+        match block["type"].as_str() {
+            Some("text") => {
+                if let Some(text) = block["text"].as_str() {
+                    content.push(ContentPart::Text {
+                        text: text.to_string(),
+                    });
+                }
+            }
+            Some("tool_use") => {
+                content.push(ContentPart::ToolCall {
+                    id: block["id"].as_str().unwrap_or("").to_string(),
+                    name: block["name"].as_str().unwrap_or("").to_string(),
+                    input: block["input"].to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let finish_reason = match json["stop_reason"].as_str() {
+        Some("end_turn") => FinishReason::EndTurn,
+        Some("max_tokens") => FinishReason::MaxTokens,
+        Some("tool_use") => FinishReason::ToolUse,
+        _ => FinishReason::EndTurn,
+    };
+
+    let usage = TokenUsage {
+        input_tokens: json["usage"]["input_tokens"].as_u64().unwrap_or(0),
+        output_tokens: json["usage"]["output_tokens"].as_u64().unwrap_or(0),
+        cache_creation_tokens: json["usage"]["cache_creation_input_tokens"]
+            .as_u64()
+            .unwrap_or(0),
+        cache_read_tokens: json["usage"]["cache_read_input_tokens"]
+            .as_u64()
+            .unwrap_or(0),
+    };
+
+    Ok(ProviderResponse {
+        content,
+        finish_reason,
+        usage,
+    })
+}
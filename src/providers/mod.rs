@@ -1,11 +1,16 @@
+mod anthropic;
 mod openai;
 
+pub use anthropic::AnthropicProvider;
 pub use openai::OpenAiProvider;
 
+#[cfg(test)]
+mod tests;
+
 use crate::core::config::AppConfig;
 use crate::core::error::ProviderError;
 use crate::core::model::{self, ModelId};
-use crate::core::provider::Provider;
+use crate::core::provider::{PingResult, Provider};
 use std::sync::Arc;
 
 /// Model role for orchestration
@@ -47,6 +52,22 @@ pub fn create_provider_for_role(
     create_provider_for_model(config, model_id)
 }
 
+/// Check connectivity for the configured provider with a minimal request.
+///
+/// Builds the default provider from config and pings it, so the CLI can
+/// diagnose a bad base URL, key, or model before a long run.
+pub async fn check_connectivity(config: &AppConfig) -> PingResult {
+    match create_provider(config, None) {
+        Ok(provider) => provider.ping().await,
+        Err(e) => PingResult {
+            auth_ok: !matches!(e, ProviderError::MissingApiKey(_)),
+            model_available: !matches!(e, ProviderError::UnsupportedModel(_)),
+            latency_ms: 0,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
 fn create_provider_for_model(
     config: &AppConfig,
     model_id: &ModelId,
@@ -84,6 +105,9 @@ fn create_provider_for_model(
             crate::core::config::ProviderType::OpenRouter => {
                 "OPENROUTER_API_KEY not set. Set via env var or config file."
             }
+            crate::core::config::ProviderType::Anthropic => {
+                "ANTHROPIC_API_KEY not set. Set via env var or config file."
+            }
             crate::core::config::ProviderType::AtlasCloud => {
                 "ATLAS_API_KEY not set. Set via env var or config file."
             }
@@ -93,10 +117,24 @@ fn create_provider_for_model(
 
     let base_url = config.get_active_base_url();
 
-    Ok(Arc::new(OpenAiProvider::new(
-        api_keys,
-        model,
-        base_url,
-        config.agent.max_tokens,
-    )))
+    if config.provider_type == crate::core::config::ProviderType::Anthropic {
+        return Ok(Arc::new(AnthropicProvider::new(
+            api_keys[0].clone(),
+            model,
+            base_url,
+            config.agent.max_tokens,
+            &config.http,
+        )));
+    }
+
+    Ok(Arc::new(
+        OpenAiProvider::new(
+            api_keys,
+            model,
+            base_url,
+            config.agent.max_tokens,
+            &config.http,
+        )
+        .with_reasoning_effort(config.agent.reasoning_effort.clone()),
+    ))
 }
@@ -7,7 +7,7 @@ use crate::core::tool::ToolDefinition;
 use reqwest::Client;
 use std::sync::Arc;
 
-const MAX_RETRIES: u32 = 8;
+pub(super) const MAX_RETRIES: u32 = 8;
 const INITIAL_BACKOFF_MS: u64 = 5_000;
 const MAX_BACKOFF_MS: u64 = 120_000;
 
@@ -17,28 +17,102 @@ pub struct OpenAiProvider {
     model: Model,
     base_url: String,
     max_tokens: u64,
+    /// `reasoning_effort` request field for reasoning models, from
+    /// `AgentConfig.reasoning_effort`; omitted from the body when `None`
+    /// or when the model doesn't support thinking
+    reasoning_effort: Option<String>,
     last_request: Arc<tokio::sync::Mutex<std::time::Instant>>,
     key_index: Arc<std::sync::atomic::AtomicUsize>,
+    temperature: Arc<std::sync::RwLock<Option<f64>>>,
+    max_tokens_override: Arc<std::sync::RwLock<Option<u64>>>,
+    tool_choice: Arc<std::sync::RwLock<Option<ToolChoice>>>,
+    rate_limit: Arc<std::sync::Mutex<RateLimitTracker>>,
+}
+
+/// Sliding window of recent request outcomes plus the active backoff
+/// deadline, feeding [`Provider::rate_limit_status`]
+struct RateLimitTracker {
+    outcomes: std::collections::VecDeque<bool>,
+    backoff_until: Option<std::time::Instant>,
+}
+
+const RATE_LIMIT_WINDOW: usize = 10;
+
+impl RateLimitTracker {
+    fn new() -> Self {
+        Self {
+            outcomes: std::collections::VecDeque::with_capacity(RATE_LIMIT_WINDOW),
+            backoff_until: None,
+        }
+    }
+
+    fn record(&mut self, limited: bool, backoff_ms: u64) {
+        if self.outcomes.len() == RATE_LIMIT_WINDOW {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back(limited);
+        self.backoff_until = if limited {
+            Some(std::time::Instant::now() + std::time::Duration::from_millis(backoff_ms))
+        } else {
+            None
+        };
+    }
+
+    fn status(&self) -> RateLimitStatus {
+        RateLimitStatus {
+            limited_recent: self.outcomes.iter().filter(|&&l| l).count(),
+            window: self.outcomes.len(),
+            backoff_remaining_ms: self
+                .backoff_until
+                .and_then(|t| t.checked_duration_since(std::time::Instant::now()))
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
 }
 
 /// Minimum interval between API requests (ms) to avoid rate limiting
 const MIN_REQUEST_INTERVAL_MS: u64 = 1500;
 
 impl OpenAiProvider {
-    pub fn new(api_keys: Vec<String>, model: Model, base_url: String, max_tokens: u64) -> Self {
+    pub fn new(
+        api_keys: Vec<String>,
+        model: Model,
+        base_url: String,
+        max_tokens: u64,
+        http: &crate::core::config::HttpConfig,
+    ) -> Self {
         Self {
-            client: Client::new(),
+            client: http.client_builder().build().unwrap_or_default(),
             api_keys,
             model,
             base_url,
             max_tokens,
+            reasoning_effort: None,
             last_request: Arc::new(tokio::sync::Mutex::new(
                 std::time::Instant::now() - std::time::Duration::from_secs(10),
             )),
             key_index: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            temperature: Arc::new(std::sync::RwLock::new(None)),
+            max_tokens_override: Arc::new(std::sync::RwLock::new(None)),
+            tool_choice: Arc::new(std::sync::RwLock::new(None)),
+            rate_limit: Arc::new(std::sync::Mutex::new(RateLimitTracker::new())),
         }
     }
 
+    pub fn with_reasoning_effort(mut self, effort: Option<String>) -> Self {
+        self.reasoning_effort = effort;
+        self
+    }
+
+    /// Effective max_tokens for the next request
+    fn effective_max_tokens(&self) -> u64 {
+        self.max_tokens_override
+            .read()
+            .unwrap()
+            .unwrap_or(self.max_tokens)
+    }
+
     /// Get the next API key using round-robin rotation
     fn next_api_key(&self) -> &str {
         if self.api_keys.len() <= 1 {
@@ -69,7 +143,7 @@ impl OpenAiProvider {
         &self,
         messages: &[Message],
         system_prompt: &str,
-    ) -> Vec<serde_json::Value> {
+    ) -> Result<Vec<serde_json::Value>, ProviderError> {
         let mut result = vec![serde_json::json!({
             "role": "system",
             "content": system_prompt,
@@ -79,6 +153,24 @@ impl OpenAiProvider {
             match msg.role {
                 MessageRole::System => continue,
                 MessageRole::User => {
+                    let has_images = msg.parts.iter().any(|p| {
+                        matches!(p, ContentPart::Image { .. } | ContentPart::ImageUrl { .. })
+                    });
+                    if has_images {
+                        // Vision models get a multimodal content array;
+                        // refusing beats silently dropping the images
+                        if !self.model.capabilities.supports_images {
+                            return Err(ProviderError::UnsupportedModel(format!(
+                                "model {} does not support image inputs",
+                                self.model.id.0
+                            )));
+                        }
+                        result.push(serde_json::json!({
+                            "role": "user",
+                            "content": multimodal_content(&msg.parts),
+                        }));
+                        continue;
+                    }
                     let text = msg.text_content();
                     if !text.is_empty() {
                         result.push(serde_json::json!({
@@ -141,7 +233,7 @@ impl OpenAiProvider {
             }
         }
 
-        result
+        Ok(result)
     }
 
     fn convert_tools(&self, tools: &[ToolDefinition]) -> Vec<serde_json::Value> {
@@ -192,23 +284,57 @@ impl Provider for OpenAiProvider {
         messages: &[Message],
         system_prompt: &str,
         tools: &[ToolDefinition],
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.send_messages_with_format(messages, system_prompt, tools, None)
+            .await
+    }
+
+    async fn send_messages_with_format(
+        &self,
+        messages: &[Message],
+        system_prompt: &str,
+        tools: &[ToolDefinition],
+        response_format: Option<&serde_json::Value>,
     ) -> Result<ProviderResponse, ProviderError> {
         self.throttle().await;
 
         let mut body = serde_json::json!({
             "model": self.model.id.0,
-            "max_tokens": self.max_tokens,
-            "messages": self.convert_messages(messages, system_prompt),
+            "max_tokens": self.effective_max_tokens(),
+            "messages": self.convert_messages(messages, system_prompt)?,
         });
 
         if !tools.is_empty() {
             body["tools"] = serde_json::json!(self.convert_tools(tools));
+            if let Some(choice) = self.tool_choice.read().unwrap().as_ref() {
+                body["tool_choice"] = choice.as_body_value();
+            }
+        }
+
+        if let Some(format) = response_format {
+            body["response_format"] = format.clone();
+        }
+
+        if let Some(temp) = *self.temperature.read().unwrap() {
+            body["temperature"] = serde_json::json!(temp);
+        }
+
+        // Only reasoning models get the field; other providers may reject
+        // unknown keys
+        if self.model.capabilities.supports_thinking {
+            if let Some(effort) = &self.reasoning_effort {
+                body["reasoning_effort"] = serde_json::json!(effort);
+            }
         }
 
         let mut last_err = ProviderError::Http("no attempts made".into());
+        // Consecutive 429s answered by rotating to another key; we only
+        // back off once every key has been limited
+        let mut keys_tried = 0usize;
+        let mut skip_backoff = false;
 
         for attempt in 0..MAX_RETRIES {
-            if attempt > 0 {
+            if attempt > 0 && !skip_backoff {
                 let backoff = compute_backoff(attempt, None);
                 eprintln!(
                     "\x1b[33m[retry {}/{}] Rate limited, waiting {:.1}s...\x1b[0m",
@@ -216,6 +342,7 @@ impl Provider for OpenAiProvider {
                 );
                 tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
             }
+            skip_backoff = false;
 
             let resp = match self
                 .client
@@ -237,6 +364,7 @@ impl Provider for OpenAiProvider {
 
             let status = resp.status().as_u16();
             if resp.status().is_success() {
+                self.rate_limit.lock().unwrap().record(false, 0);
                 let api_resp: serde_json::Value = resp
                     .json()
                     .await
@@ -249,14 +377,23 @@ impl Provider for OpenAiProvider {
                 .headers()
                 .get("retry-after")
                 .and_then(|v| v.to_str().ok())
-                .and_then(|v| v.parse::<u64>().ok())
-                .map(|secs| secs * 1000);
+                .and_then(parse_retry_after);
 
             let text = resp.text().await.unwrap_or_default();
             if status == 429 || status == 502 || status == 503 {
+                let wait = retry_after.unwrap_or(compute_backoff(attempt + 1, None));
+                self.rate_limit.lock().unwrap().record(true, wait);
                 last_err = ProviderError::RateLimited {
-                    retry_after_ms: retry_after.unwrap_or(compute_backoff(attempt + 1, None)),
+                    retry_after_ms: wait,
                 };
+                // A 429 may be per-key: retry immediately with the next
+                // key and reserve the backoff for when all are limited
+                keys_tried += 1;
+                if status == 429 && keys_tried < self.api_keys.len() {
+                    skip_backoff = true;
+                } else {
+                    keys_tried = 0;
+                }
                 continue;
             }
             return Err(ProviderError::Api {
@@ -278,20 +415,39 @@ impl Provider for OpenAiProvider {
 
         let mut body = serde_json::json!({
             "model": self.model.id.0,
-            "max_tokens": self.max_tokens,
-            "messages": self.convert_messages(messages, system_prompt),
+            "max_tokens": self.effective_max_tokens(),
+            "messages": self.convert_messages(messages, system_prompt)?,
             "stream": true,
         });
 
         if !tools.is_empty() {
             body["tools"] = serde_json::json!(self.convert_tools(tools));
+            if let Some(choice) = self.tool_choice.read().unwrap().as_ref() {
+                body["tool_choice"] = choice.as_body_value();
+            }
+        }
+
+        if let Some(temp) = *self.temperature.read().unwrap() {
+            body["temperature"] = serde_json::json!(temp);
+        }
+
+        // Only reasoning models get the field; other providers may reject
+        // unknown keys
+        if self.model.capabilities.supports_thinking {
+            if let Some(effort) = &self.reasoning_effort {
+                body["reasoning_effort"] = serde_json::json!(effort);
+            }
         }
 
         let mut last_err = ProviderError::Http("no attempts made".into());
+        // Consecutive 429s answered by rotating to another key; we only
+        // back off once every key has been limited
+        let mut keys_tried = 0usize;
+        let mut skip_backoff = false;
         let mut resp_ok = None;
 
         for attempt in 0..MAX_RETRIES {
-            if attempt > 0 {
+            if attempt > 0 && !skip_backoff {
                 let backoff = compute_backoff(attempt, None);
                 eprintln!(
                     "\x1b[33m[retry {}/{}] Rate limited, waiting {:.1}s...\x1b[0m",
@@ -299,6 +455,7 @@ impl Provider for OpenAiProvider {
                 );
                 tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
             }
+            skip_backoff = false;
 
             let resp = match self
                 .client
@@ -320,6 +477,7 @@ impl Provider for OpenAiProvider {
 
             let status = resp.status().as_u16();
             if resp.status().is_success() {
+                self.rate_limit.lock().unwrap().record(false, 0);
                 resp_ok = Some(resp);
                 break;
             }
@@ -328,14 +486,23 @@ impl Provider for OpenAiProvider {
                 .headers()
                 .get("retry-after")
                 .and_then(|v| v.to_str().ok())
-                .and_then(|v| v.parse::<u64>().ok())
-                .map(|secs| secs * 1000);
+                .and_then(parse_retry_after);
 
             let text = resp.text().await.unwrap_or_default();
             if status == 429 || status == 502 || status == 503 {
+                let wait = retry_after.unwrap_or(compute_backoff(attempt + 1, None));
+                self.rate_limit.lock().unwrap().record(true, wait);
                 last_err = ProviderError::RateLimited {
-                    retry_after_ms: retry_after.unwrap_or(compute_backoff(attempt + 1, None)),
+                    retry_after_ms: wait,
                 };
+                // A 429 may be per-key: retry immediately with the next
+                // key and reserve the backoff for when all are limited
+                keys_tried += 1;
+                if status == 429 && keys_tried < self.api_keys.len() {
+                    skip_backoff = true;
+                } else {
+                    keys_tried = 0;
+                }
                 continue;
             }
             return Err(ProviderError::Api {
@@ -357,7 +524,7 @@ impl Provider for OpenAiProvider {
                 std::collections::HashMap::new();
             let mut has_content = false;
 
-            while let Some(chunk) = byte_stream.next().await {
+            'outer: while let Some(chunk) = byte_stream.next().await {
                 let chunk = match chunk {
                     Ok(c) => c,
                     Err(e) => {
@@ -388,11 +555,35 @@ impl Provider for OpenAiProvider {
                         Err(_) => continue,
                     };
 
+                    // Gateways can emit an error object mid-stream (or as the
+                    // only event) on a 200 response; surface its message
+                    // instead of silently ending the stream
+                    if json.get("choices").is_none() {
+                        if let Some(msg) = gateway_error_message(&json) {
+                            yield ProviderEvent::Error {
+                                error: ProviderError::Stream(format!(
+                                    "gateway error: {msg}"
+                                )),
+                            };
+                            break 'outer;
+                        }
+                    }
+
                     if let Some(choices) = json["choices"].as_array() {
                         for choice in choices {
                             let delta = &choice["delta"];
                             let finish_reason = choice["finish_reason"].as_str();
 
+                            // Reasoning delta — DeepSeek and GLM stream
+                            // thinking as a separate field
+                            if let Some(text) = delta["reasoning_content"].as_str() {
+                                if !text.is_empty() {
+                                    yield ProviderEvent::ThinkingDelta {
+                                        text: text.to_string(),
+                                    };
+                                }
+                            }
+
                             // Content delta
                             if let Some(text) = delta["content"].as_str() {
                                 if !has_content {
@@ -474,7 +665,10 @@ impl Provider for OpenAiProvider {
                                             .as_u64()
                                             .unwrap_or(0),
                                         cache_creation_tokens: 0,
-                                        cache_read_tokens: 0,
+                                        cache_read_tokens: u["prompt_tokens_details"]
+                                            ["cached_tokens"]
+                                            .as_u64()
+                                            .unwrap_or(0),
                                     }
                                 } else {
                                     TokenUsage::default()
@@ -497,10 +691,83 @@ impl Provider for OpenAiProvider {
     fn model(&self) -> &Model {
         &self.model
     }
+
+    fn set_tool_choice(&self, choice: Option<ToolChoice>) {
+        *self.tool_choice.write().unwrap() = choice;
+    }
+
+    fn tool_choice(&self) -> Option<ToolChoice> {
+        self.tool_choice.read().unwrap().clone()
+    }
+
+    fn rate_limit_status(&self) -> RateLimitStatus {
+        self.rate_limit.lock().unwrap().status()
+    }
+
+    fn set_temperature(&self, temperature: Option<f64>) {
+        *self.temperature.write().unwrap() = temperature;
+    }
+
+    fn temperature(&self) -> Option<f64> {
+        *self.temperature.read().unwrap()
+    }
+
+    fn set_max_tokens_override(&self, max_tokens: Option<u64>) {
+        *self.max_tokens_override.write().unwrap() = max_tokens;
+    }
+
+    fn max_tokens_override(&self) -> Option<u64> {
+        *self.max_tokens_override.read().unwrap()
+    }
+}
+
+/// Build an OpenAI-style multimodal `content` array from a user message
+/// containing image parts. Inline images become `data:` URLs.
+fn multimodal_content(parts: &[ContentPart]) -> Vec<serde_json::Value> {
+    let mut content = Vec::new();
+    for part in parts {
+        match part {
+            ContentPart::Text { text } => {
+                content.push(serde_json::json!({"type": "text", "text": text}));
+            }
+            ContentPart::Image { data, media_type } => {
+                content.push(serde_json::json!({
+                    "type": "image_url",
+                    "image_url": {"url": format!("data:{media_type};base64,{data}")},
+                }));
+            }
+            ContentPart::ImageUrl { url, detail } => {
+                let mut image_url = serde_json::json!({"url": url});
+                if let Some(detail) = detail {
+                    image_url["detail"] = serde_json::json!(detail);
+                }
+                content.push(serde_json::json!({
+                    "type": "image_url",
+                    "image_url": image_url,
+                }));
+            }
+            _ => {}
+        }
+    }
+    content
+}
+
+/// Parse a `Retry-After` header value as either delta-seconds or an
+/// HTTP-date, returning milliseconds to wait clamped to [`MAX_BACKOFF_MS`].
+/// Dates in the past yield 0.
+pub(super) fn parse_retry_after(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some((secs * 1000).min(MAX_BACKOFF_MS));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delta = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    let ms = delta.num_milliseconds().max(0) as u64;
+    Some(ms.min(MAX_BACKOFF_MS))
 }
 
 /// Exponential backoff with jitter to avoid thundering herd
-fn compute_backoff(attempt: u32, server_retry_ms: Option<u64>) -> u64 {
+pub(super) fn compute_backoff(attempt: u32, server_retry_ms: Option<u64>) -> u64 {
     if let Some(ms) = server_retry_ms {
         return ms;
     }
@@ -511,21 +778,59 @@ fn compute_backoff(attempt: u32, server_retry_ms: Option<u64>) -> u64 {
     capped + jitter
 }
 
-/// Simple pseudo-random float [0, 1) using thread-local state
+/// Pseudo-random float in [0, 1) from a thread-local xorshift64 state.
+/// Each thread seeds once through a randomly-keyed hasher, so concurrent
+/// retries across agents get independent jitter instead of all deriving
+/// the same value from the wall clock.
 fn rand_f64() -> f64 {
-    use std::time::SystemTime;
-    let seed = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap_or_default()
-        .subsec_nanos();
-    (seed as f64 % 1000.0) / 1000.0
+    use std::cell::Cell;
+
+    fn seed() -> u64 {
+        use std::hash::BuildHasher;
+        let state = std::collections::hash_map::RandomState::new();
+        // xorshift must never be seeded with zero
+        state.hash_one(std::thread::current().id()) | 1
+    }
+
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(seed());
+    }
+
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+/// Extract the error text from a gateway error body. Some gateways return
+/// HTTP 200 with an `error` object (or a bare `message`) instead of `choices`.
+fn gateway_error_message(json: &serde_json::Value) -> Option<String> {
+    if let Some(err) = json.get("error") {
+        if let Some(msg) = err["message"].as_str() {
+            return Some(msg.to_string());
+        }
+        if let Some(msg) = err.as_str() {
+            return Some(msg.to_string());
+        }
+        return Some(err.to_string());
+    }
+    json["message"].as_str().map(|m| m.to_string())
 }
 
-fn parse_openai_response(json: serde_json::Value) -> Result<ProviderResponse, ProviderError> {
+pub(super) fn parse_openai_response(
+    json: serde_json::Value,
+) -> Result<ProviderResponse, ProviderError> {
     let choice = json["choices"]
         .as_array()
         .and_then(|c| c.first())
-        .ok_or_else(|| ProviderError::Stream("No choices in response".into()))?;
+        .ok_or_else(|| match gateway_error_message(&json) {
+            Some(msg) => ProviderError::Stream(format!("gateway error: {msg}")),
+            None => ProviderError::Stream("No choices in response".into()),
+        })?;
 
     let message = &choice["message"];
     let mut content = Vec::new();
@@ -560,7 +865,9 @@ fn parse_openai_response(json: serde_json::Value) -> Result<ProviderResponse, Pr
         input_tokens: json["usage"]["prompt_tokens"].as_u64().unwrap_or(0),
         output_tokens: json["usage"]["completion_tokens"].as_u64().unwrap_or(0),
         cache_creation_tokens: 0,
-        cache_read_tokens: 0,
+        cache_read_tokens: json["usage"]["prompt_tokens_details"]["cached_tokens"]
+            .as_u64()
+            .unwrap_or(0),
     };
 
     Ok(ProviderResponse {
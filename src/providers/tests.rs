@@ -0,0 +1,475 @@
+use super::{AnthropicProvider, OpenAiProvider};
+use crate::core::config::HttpConfig;
+use crate::core::message::Message;
+use crate::core::model::{get_model, ModelId};
+use crate::core::provider::Provider;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Minimal HTTP server that records each raw request and answers with the
+/// next scripted status code (200 responses carry a valid chat completion
+/// body)
+async fn mock_api(
+    statuses: Vec<u16>,
+) -> (
+    String,
+    Arc<Mutex<Vec<String>>>,
+    tokio::task::JoinHandle<()>,
+) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let requests_clone = Arc::clone(&requests);
+    let handle = tokio::spawn(async move {
+        let mut statuses = statuses.into_iter();
+        loop {
+            let (mut sock, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(_) => return,
+            };
+
+            // Read headers plus the Content-Length body
+            let mut buf = Vec::new();
+            let mut tmp = [0u8; 4096];
+            let request = loop {
+                let n = match sock.read(&mut tmp).await {
+                    Ok(0) | Err(_) => break None,
+                    Ok(n) => n,
+                };
+                buf.extend_from_slice(&tmp[..n]);
+                let text = String::from_utf8_lossy(&buf).to_string();
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text
+                        .lines()
+                        .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(str::trim).and_then(|v| v.parse::<usize>().ok()))
+                        .unwrap_or(0);
+                    if buf.len() >= header_end + 4 + content_length {
+                        break Some(text);
+                    }
+                }
+            };
+            let Some(request) = request else { continue };
+            requests_clone.lock().unwrap().push(request);
+
+            let status = statuses.next().unwrap_or(200);
+            let (status_line, body) = if status == 200 {
+                (
+                    "HTTP/1.1 200 OK",
+                    r#"{"choices":[{"message":{"content":"ok"},"finish_reason":"stop"}],"usage":{"prompt_tokens":1,"completion_tokens":1}}"#,
+                )
+            } else {
+                ("HTTP/1.1 429 Too Many Requests", "{}")
+            };
+            let response = format!(
+                "{status_line}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = sock.write_all(response.as_bytes()).await;
+        }
+    });
+
+    (base_url, requests, handle)
+}
+
+/// Bearer tokens of the recorded requests, in order
+fn bearer_tokens(requests: &Mutex<Vec<String>>) -> Vec<String> {
+    requests
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|req| {
+            req.lines()
+                .find_map(|l| {
+                    l.strip_prefix("authorization: Bearer ")
+                        .or_else(|| l.strip_prefix("Authorization: Bearer "))
+                })
+                .unwrap_or("")
+                .to_string()
+        })
+        .collect()
+}
+
+fn provider(keys: Vec<&str>, base_url: String) -> OpenAiProvider {
+    OpenAiProvider::new(
+        keys.into_iter().map(String::from).collect(),
+        get_model(&ModelId("zai-org/glm-5".into())).unwrap(),
+        base_url,
+        1024,
+        &HttpConfig::default(),
+    )
+}
+
+#[tokio::test]
+async fn test_requests_rotate_across_api_keys() {
+    let (base_url, requests, server) = mock_api(vec![200, 200, 200, 200]).await;
+    let provider = provider(vec!["k1", "k2", "k3"], base_url);
+
+    let messages = [Message::new_user("s1".into(), "hi".into())];
+    for _ in 0..4 {
+        provider
+            .send_messages(&messages, "prompt", &[])
+            .await
+            .unwrap();
+    }
+
+    // Round-robin: cycles through all keys and wraps around
+    assert_eq!(bearer_tokens(&requests), vec!["k1", "k2", "k3", "k1"]);
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_rate_limited_key_switches_without_backoff() {
+    // First key gets a 429; the retry must use the next key immediately
+    // rather than sleeping through the multi-second backoff
+    let (base_url, requests, server) = mock_api(vec![429, 200]).await;
+    let provider = provider(vec!["k1", "k2"], base_url);
+
+    let messages = [Message::new_user("s1".into(), "hi".into())];
+    let start = std::time::Instant::now();
+    let response = provider
+        .send_messages(&messages, "prompt", &[])
+        .await
+        .unwrap();
+
+    assert!(!response.content.is_empty());
+    assert_eq!(bearer_tokens(&requests), vec!["k1", "k2"]);
+    assert!(
+        start.elapsed() < std::time::Duration::from_secs(4),
+        "key switch must not wait out the backoff"
+    );
+    server.abort();
+}
+
+#[test]
+fn test_parse_retry_after_both_formats() {
+    use super::openai::parse_retry_after;
+
+    // Integer delta-seconds
+    assert_eq!(parse_retry_after("30"), Some(30_000));
+    assert_eq!(parse_retry_after(" 5 "), Some(5_000));
+    // Clamped to the backoff ceiling (120s)
+    assert_eq!(parse_retry_after("600"), Some(120_000));
+
+    // HTTP-date: a timestamp ~10s out waits roughly that long
+    let future = (chrono::Utc::now() + chrono::Duration::seconds(10)).to_rfc2822();
+    let ms = parse_retry_after(&future).unwrap();
+    assert!((8_000..=11_000).contains(&ms), "got {ms}");
+
+    // HTTP-date with the GMT zone name Atlas Cloud sends
+    let gmt = (chrono::Utc::now() + chrono::Duration::seconds(10))
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+    let ms = parse_retry_after(&gmt).unwrap();
+    assert!((8_000..=11_000).contains(&ms), "got {ms}");
+
+    // Dates in the past mean "retry now", not a huge backoff
+    let past = (chrono::Utc::now() - chrono::Duration::seconds(60)).to_rfc2822();
+    assert_eq!(parse_retry_after(&past), Some(0));
+
+    // Garbage falls through to exponential backoff
+    assert_eq!(parse_retry_after("soon"), None);
+}
+
+#[test]
+fn test_backoff_jitter_is_well_distributed() {
+    use super::openai::compute_backoff;
+
+    // Base for attempt 1 is 5000ms with 0-25% jitter on top
+    let values: Vec<u64> = (0..200).map(|_| compute_backoff(1, None)).collect();
+    for &v in &values {
+        assert!((5_000..=6_250).contains(&v), "jitter out of range: {v}");
+    }
+
+    // A degenerate RNG collapses to a handful of values; a real one
+    // spreads across the jitter window
+    let distinct: std::collections::HashSet<u64> = values.iter().copied().collect();
+    assert!(
+        distinct.len() > 50,
+        "expected well-distributed jitter, got {} distinct values",
+        distinct.len()
+    );
+}
+
+#[tokio::test]
+async fn test_single_key_still_works() {
+    let (base_url, requests, server) = mock_api(vec![200]).await;
+    let provider = provider(vec!["only-key"], base_url);
+
+    let messages = [Message::new_user("s1".into(), "hi".into())];
+    provider
+        .send_messages(&messages, "prompt", &[])
+        .await
+        .unwrap();
+
+    assert_eq!(bearer_tokens(&requests), vec!["only-key"]);
+    server.abort();
+}
+
+/// One-shot server that replies to the first request with a scripted SSE
+/// stream and closes
+async fn mock_sse_api(events: Vec<String>) -> (String, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        let (mut sock, _) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(_) => return,
+        };
+        // Drain the request headers before responding
+        let mut tmp = [0u8; 4096];
+        let _ = sock.read(&mut tmp).await;
+
+        let body: String = events
+            .iter()
+            .map(|e| format!("data: {e}\n\n"))
+            .collect::<String>()
+            + "data: [DONE]\n\n";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let _ = sock.write_all(response.as_bytes()).await;
+    });
+
+    (base_url, handle)
+}
+
+#[tokio::test]
+async fn test_stream_parses_reasoning_content_deltas() {
+    use crate::core::provider::ProviderEvent;
+    use tokio_stream::StreamExt;
+
+    let (base_url, server) = mock_sse_api(vec![
+        r#"{"choices":[{"delta":{"reasoning_content":"think"}}]}"#.into(),
+        r#"{"choices":[{"delta":{"reasoning_content":"ing"}}]}"#.into(),
+        r#"{"choices":[{"delta":{"content":"answer"}}]}"#.into(),
+        r#"{"choices":[{"delta":{},"finish_reason":"stop"}]}"#.into(),
+    ])
+    .await;
+    let provider = provider(vec!["k1"], base_url);
+
+    let messages = [Message::new_user("s1".into(), "hi".into())];
+    let mut stream = provider
+        .stream_response(&messages, "prompt", &[])
+        .await
+        .unwrap();
+
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        events.push(event);
+    }
+    server.abort();
+
+    let summary: Vec<String> = events
+        .iter()
+        .map(|e| match e {
+            ProviderEvent::ThinkingDelta { text } => format!("thinking:{text}"),
+            ProviderEvent::ContentStart => "content_start".into(),
+            ProviderEvent::ContentDelta { text } => format!("content:{text}"),
+            ProviderEvent::ContentStop => "content_stop".into(),
+            ProviderEvent::Complete { .. } => "complete".into(),
+            _ => "other".into(),
+        })
+        .collect();
+
+    // Reasoning deltas surface before content starts
+    assert_eq!(
+        summary,
+        vec![
+            "thinking:think",
+            "thinking:ing",
+            "content_start",
+            "content:answer",
+            "content_stop",
+            "complete",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_image_parts_sent_as_multimodal_content() {
+    use crate::core::message::ContentPart;
+
+    let (base_url, requests, server) = mock_api(vec![200]).await;
+    let mut model = get_model(&ModelId("zai-org/glm-5".into())).unwrap();
+    model.capabilities.supports_images = true;
+    let provider = OpenAiProvider::new(
+        vec!["k1".into()],
+        model.clone(),
+        base_url,
+        1024,
+        &HttpConfig::default(),
+    );
+
+    let mut msg = Message::new_user("s1".into(), "what is this?".into());
+    msg.parts.push(ContentPart::ImageUrl {
+        url: "https://example.com/cat.png".into(),
+        detail: Some("low".into()),
+    });
+
+    provider
+        .send_messages(&[msg.clone()], "prompt", &[])
+        .await
+        .unwrap();
+    let request = requests.lock().unwrap()[0].clone();
+    assert!(request.contains(r#""type":"image_url""#));
+    assert!(request.contains(r#""url":"https://example.com/cat.png""#));
+    assert!(request.contains(r#""detail":"low""#));
+    server.abort();
+
+    // Same message against a non-vision model errors instead of silently
+    // dropping the image
+    let (base_url, _requests, server) = mock_api(vec![200]).await;
+    model.capabilities.supports_images = false;
+    let provider = OpenAiProvider::new(
+        vec!["k1".into()],
+        model,
+        base_url,
+        1024,
+        &HttpConfig::default(),
+    );
+    let err = provider.send_messages(&[msg], "prompt", &[]).await;
+    assert!(matches!(
+        err,
+        Err(crate::core::error::ProviderError::UnsupportedModel(_))
+    ));
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_anthropic_stream_parses_tool_use_blocks() {
+    use crate::core::provider::ProviderEvent;
+    use tokio_stream::StreamExt;
+
+    let (base_url, server) = mock_sse_api(vec![
+        r#"{"type":"message_start","message":{"usage":{"input_tokens":12}}}"#.into(),
+        r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#.into(),
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Let me check."}}"#.into(),
+        r#"{"type":"content_block_stop","index":0}"#.into(),
+        r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"tu_1","name":"bash","input":{}}}"#.into(),
+        r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":"}}"#.into(),
+        r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"ls\"}"}}"#.into(),
+        r#"{"type":"content_block_stop","index":1}"#.into(),
+        r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":7}}"#.into(),
+        r#"{"type":"message_stop"}"#.into(),
+    ])
+    .await;
+
+    let provider = AnthropicProvider::new(
+        "k1".into(),
+        get_model(&ModelId("zai-org/glm-5".into())).unwrap(),
+        base_url,
+        1024,
+        &HttpConfig::default(),
+    );
+
+    let messages = [Message::new_user("s1".into(), "hi".into())];
+    let mut stream = provider
+        .stream_response(&messages, "prompt", &[])
+        .await
+        .unwrap();
+
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        events.push(event);
+    }
+    server.abort();
+
+    let summary: Vec<String> = events
+        .iter()
+        .map(|e| match e {
+            ProviderEvent::ContentStart => "content_start".into(),
+            ProviderEvent::ContentDelta { text } => format!("content:{text}"),
+            ProviderEvent::ContentStop => "content_stop".into(),
+            ProviderEvent::ToolUseStart { id, name } => format!("tool_start:{id}:{name}"),
+            ProviderEvent::ToolUseDelta { input_json_chunk } => {
+                format!("tool_delta:{input_json_chunk}")
+            }
+            ProviderEvent::ToolUseStop => "tool_stop".into(),
+            ProviderEvent::Complete {
+                finish_reason,
+                usage,
+            } => format!(
+                "complete:{finish_reason:?}:{}:{}",
+                usage.input_tokens, usage.output_tokens
+            ),
+            _ => "other".into(),
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            "content_start",
+            "content:Let me check.",
+            "content_stop",
+            "tool_start:tu_1:bash",
+            "tool_delta:{\"command\":",
+            "tool_delta:\"ls\"}",
+            "tool_stop",
+            "complete:ToolUse:12:7",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_reasoning_effort_sent_only_for_thinking_models() {
+    // glm-5 supports thinking: the field lands in the body
+    let (base_url, requests, server) = mock_api(vec![200]).await;
+    let provider =
+        provider(vec!["k1"], base_url).with_reasoning_effort(Some("high".into()));
+    let messages = [Message::new_user("s1".into(), "hi".into())];
+    provider
+        .send_messages(&messages, "prompt", &[])
+        .await
+        .unwrap();
+    assert!(requests.lock().unwrap()[0].contains(r#""reasoning_effort":"high""#));
+    server.abort();
+
+    // Non-thinking model omits it even when configured
+    let (base_url, requests, server) = mock_api(vec![200]).await;
+    let provider = OpenAiProvider::new(
+        vec!["k1".into()],
+        get_model(&ModelId("Qwen/Qwen3-Coder".into())).unwrap(),
+        base_url,
+        1024,
+        &HttpConfig::default(),
+    )
+    .with_reasoning_effort(Some("high".into()));
+    provider
+        .send_messages(&messages, "prompt", &[])
+        .await
+        .unwrap();
+    assert!(!requests.lock().unwrap()[0].contains("reasoning_effort"));
+    server.abort();
+}
+
+#[test]
+fn test_response_usage_includes_cached_tokens() {
+    let json = serde_json::json!({
+        "choices": [{
+            "message": {"content": "ok"},
+            "finish_reason": "stop"
+        }],
+        "usage": {
+            "prompt_tokens": 1000,
+            "completion_tokens": 50,
+            "prompt_tokens_details": {"cached_tokens": 400}
+        }
+    });
+
+    let response = super::openai::parse_openai_response(json).unwrap();
+    assert_eq!(response.usage.input_tokens, 1000);
+    assert_eq!(response.usage.output_tokens, 50);
+    assert_eq!(response.usage.cache_read_tokens, 400);
+
+    // Responses without the details object keep reporting zero
+    let json = serde_json::json!({
+        "choices": [{"message": {"content": "ok"}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 5}
+    });
+    let response = super::openai::parse_openai_response(json).unwrap();
+    assert_eq!(response.usage.cache_read_tokens, 0);
+}
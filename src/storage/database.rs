@@ -35,6 +35,27 @@ impl Database {
             .execute(&self.pool)
             .await
             .map_err(|e| StorageError::Migration(e.to_string()))?;
+
+        // Additive column migrations run statement by statement; SQLite's
+        // ALTER TABLE has no IF NOT EXISTS, so duplicate-column errors on
+        // re-run are expected and ignored
+        let additive = [
+            include_str!("../../migrations/002_session_model.sql"),
+            include_str!("../../migrations/003_session_changed_files.sql"),
+        ];
+        for statement in additive
+            .iter()
+            .flat_map(|sql| sql.split(';'))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            if let Err(e) = sqlx::query(statement).execute(&self.pool).await {
+                let msg = e.to_string();
+                if !msg.contains("duplicate column name") {
+                    return Err(StorageError::Migration(msg));
+                }
+            }
+        }
         Ok(())
     }
 
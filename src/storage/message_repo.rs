@@ -79,6 +79,29 @@ impl MessageRepo {
         rows.into_iter().map(row_to_message).collect()
     }
 
+    /// Delete the most recent user message and everything after it (the
+    /// superseded assistant/tool turns), for edit-and-regenerate.
+    pub async fn delete_last_turn(&self, session_id: &str) -> Result<(), StorageError> {
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT created_at FROM messages WHERE session_id = ? AND role = 'user' \
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        if let Some((created_at,)) = row {
+            sqlx::query("DELETE FROM messages WHERE session_id = ? AND created_at >= ?")
+                .bind(session_id)
+                .bind(&created_at)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        Ok(())
+    }
+
     pub async fn delete_session_messages(&self, session_id: &str) -> Result<(), StorageError> {
         sqlx::query("DELETE FROM messages WHERE session_id = ?")
             .bind(session_id)
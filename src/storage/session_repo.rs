@@ -7,6 +7,20 @@ pub struct SessionRepo {
     pool: SqlitePool,
 }
 
+type SessionRow = (
+    String,
+    String,
+    i64,
+    i64,
+    i64,
+    f64,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+    String,
+);
+
 impl SessionRepo {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
@@ -15,8 +29,8 @@ impl SessionRepo {
     pub async fn create(&self, session: &Session) -> Result<(), StorageError> {
         sqlx::query(
             "INSERT INTO sessions (id, title, message_count, prompt_tokens, \
-             completion_tokens, cost, created_at, updated_at) \
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+             completion_tokens, cost, model_id, provider, changed_files, created_at, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&session.id)
         .bind(&session.title)
@@ -24,6 +38,9 @@ impl SessionRepo {
         .bind(session.prompt_tokens as i64)
         .bind(session.completion_tokens as i64)
         .bind(session.cost)
+        .bind(&session.model_id)
+        .bind(&session.provider)
+        .bind(serde_json::to_string(&session.changed_files).unwrap_or_default())
         .bind(session.created_at.to_rfc3339())
         .bind(session.updated_at.to_rfc3339())
         .execute(&self.pool)
@@ -33,9 +50,9 @@ impl SessionRepo {
     }
 
     pub async fn get(&self, id: &str) -> Result<Session, StorageError> {
-        let row: (String, String, i64, i64, i64, f64, String, String) = sqlx::query_as(
+        let row: SessionRow = sqlx::query_as(
             "SELECT id, title, message_count, prompt_tokens, \
-             completion_tokens, cost, created_at, updated_at \
+             completion_tokens, cost, model_id, provider, changed_files, created_at, updated_at \
              FROM sessions WHERE id = ?",
         )
         .bind(id)
@@ -48,9 +65,9 @@ impl SessionRepo {
     }
 
     pub async fn list(&self) -> Result<Vec<Session>, StorageError> {
-        let rows: Vec<(String, String, i64, i64, i64, f64, String, String)> = sqlx::query_as(
+        let rows: Vec<SessionRow> = sqlx::query_as(
             "SELECT id, title, message_count, prompt_tokens, \
-             completion_tokens, cost, created_at, updated_at \
+             completion_tokens, cost, model_id, provider, changed_files, created_at, updated_at \
              FROM sessions ORDER BY updated_at DESC",
         )
         .fetch_all(&self.pool)
@@ -64,6 +81,7 @@ impl SessionRepo {
         sqlx::query(
             "UPDATE sessions SET title = ?, message_count = ?, \
              prompt_tokens = ?, completion_tokens = ?, cost = ?, \
+             model_id = ?, provider = ?, changed_files = ?, \
              updated_at = ? WHERE id = ?",
         )
         .bind(&session.title)
@@ -71,6 +89,59 @@ impl SessionRepo {
         .bind(session.prompt_tokens as i64)
         .bind(session.completion_tokens as i64)
         .bind(session.cost)
+        .bind(&session.model_id)
+        .bind(&session.provider)
+        .bind(serde_json::to_string(&session.changed_files).unwrap_or_default())
+        .bind(Utc::now().to_rfc3339())
+        .bind(&session.id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Accumulate usage counters at the DB level (`SET cost = cost + ?`)
+    /// so concurrent writers to the same session — auto-save, auto-titling,
+    /// completions — can't clobber each other's totals.
+    pub async fn add_usage(
+        &self,
+        id: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost: f64,
+        messages: u64,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "UPDATE sessions SET \
+             prompt_tokens = prompt_tokens + ?, \
+             completion_tokens = completion_tokens + ?, \
+             cost = cost + ?, \
+             message_count = message_count + ?, \
+             updated_at = ? WHERE id = ?",
+        )
+        .bind(prompt_tokens as i64)
+        .bind(completion_tokens as i64)
+        .bind(cost)
+        .bind(messages as i64)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Update only the non-cumulative columns, leaving the usage counters
+    /// to [`add_usage`](Self::add_usage).
+    pub async fn update_meta(&self, session: &Session) -> Result<(), StorageError> {
+        sqlx::query(
+            "UPDATE sessions SET title = ?, model_id = ?, provider = ?, \
+             changed_files = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(&session.title)
+        .bind(&session.model_id)
+        .bind(&session.provider)
+        .bind(serde_json::to_string(&session.changed_files).unwrap_or_default())
         .bind(Utc::now().to_rfc3339())
         .bind(&session.id)
         .execute(&self.pool)
@@ -89,7 +160,7 @@ impl SessionRepo {
     }
 }
 
-fn row_to_session(row: (String, String, i64, i64, i64, f64, String, String)) -> Session {
+fn row_to_session(row: SessionRow) -> Session {
     Session {
         id: row.0,
         title: row.1,
@@ -97,10 +168,17 @@ fn row_to_session(row: (String, String, i64, i64, i64, f64, String, String)) ->
         prompt_tokens: row.3 as u64,
         completion_tokens: row.4 as u64,
         cost: row.5,
-        created_at: DateTime::parse_from_rfc3339(&row.6)
+        model_id: row.6,
+        provider: row.7,
+        changed_files: row
+            .8
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default(),
+        created_at: DateTime::parse_from_rfc3339(&row.9)
             .unwrap_or_default()
             .with_timezone(&Utc),
-        updated_at: DateTime::parse_from_rfc3339(&row.7)
+        updated_at: DateTime::parse_from_rfc3339(&row.10)
             .unwrap_or_default()
             .with_timezone(&Utc),
     }
@@ -38,11 +38,17 @@ async fn test_session_crud() {
     updated.title = "Updated title".into();
     updated.message_count = 5;
     updated.cost = 0.01;
+    updated.model_id = Some("zai-org/glm-5".into());
+    updated.provider = Some("atlas_cloud".into());
+    updated.changed_files = vec!["src/main.rs".into(), "README.md".into()];
     db.sessions().update(&updated).await.unwrap();
 
     let fetched2 = db.sessions().get(&session.id).await.unwrap();
     assert_eq!(fetched2.title, "Updated title");
     assert_eq!(fetched2.message_count, 5);
+    assert_eq!(fetched2.model_id.as_deref(), Some("zai-org/glm-5"));
+    assert_eq!(fetched2.provider.as_deref(), Some("atlas_cloud"));
+    assert_eq!(fetched2.changed_files, vec!["src/main.rs", "README.md"]);
 
     // Delete
     db.sessions().delete(&session.id).await.unwrap();
@@ -50,6 +56,38 @@ async fn test_session_crud() {
     assert_eq!(all.len(), 0);
 }
 
+#[tokio::test]
+async fn test_add_usage_accumulates() {
+    let (db, _tmp) = test_db().await;
+
+    let session = Session::new("Usage test".into());
+    db.sessions().create(&session).await.unwrap();
+
+    db.sessions()
+        .add_usage(&session.id, 100, 50, 0.01, 2)
+        .await
+        .unwrap();
+    db.sessions()
+        .add_usage(&session.id, 30, 20, 0.005, 1)
+        .await
+        .unwrap();
+
+    let fetched = db.sessions().get(&session.id).await.unwrap();
+    assert_eq!(fetched.prompt_tokens, 130);
+    assert_eq!(fetched.completion_tokens, 70);
+    assert!((fetched.cost - 0.015).abs() < 1e-9);
+    assert_eq!(fetched.message_count, 3);
+
+    // update_meta leaves the counters alone
+    let mut meta = fetched;
+    meta.title = "Renamed".into();
+    db.sessions().update_meta(&meta).await.unwrap();
+    let fetched2 = db.sessions().get(&session.id).await.unwrap();
+    assert_eq!(fetched2.title, "Renamed");
+    assert_eq!(fetched2.prompt_tokens, 130);
+    assert_eq!(fetched2.message_count, 3);
+}
+
 #[tokio::test]
 async fn test_message_crud() {
     let (db, _tmp) = test_db().await;
@@ -128,6 +166,46 @@ async fn test_message_crud() {
     assert_eq!(messages.len(), 0);
 }
 
+#[tokio::test]
+async fn test_delete_last_turn() {
+    let (db, _tmp) = test_db().await;
+
+    let session = Session::new("Turn test".into());
+    db.sessions().create(&session).await.unwrap();
+
+    let add_turn = |prompt: &str, reply: &str| {
+        let user = Message::new_user(session.id.clone(), prompt.into());
+        let mut assistant =
+            Message::new_assistant(session.id.clone(), ModelId("test".into()));
+        assistant.parts.push(ContentPart::Text { text: reply.into() });
+        (user, assistant)
+    };
+
+    let (user1, assistant1) = add_turn("first prompt", "first reply");
+    db.messages().create(&user1).await.unwrap();
+    db.messages().create(&assistant1).await.unwrap();
+    // Ensure the second turn sorts strictly after the first
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    let (user2, assistant2) = add_turn("second prompt", "second reply");
+    db.messages().create(&user2).await.unwrap();
+    db.messages().create(&assistant2).await.unwrap();
+
+    db.messages().delete_last_turn(&session.id).await.unwrap();
+
+    let remaining = db.messages().list(&session.id).await.unwrap();
+    assert_eq!(remaining.len(), 2);
+    assert_eq!(remaining[0].text_content(), "first prompt");
+    assert_eq!(remaining[1].text_content(), "first reply");
+
+    // Deleting again removes the remaining turn
+    db.messages().delete_last_turn(&session.id).await.unwrap();
+    let remaining = db.messages().list(&session.id).await.unwrap();
+    assert_eq!(remaining.len(), 0);
+
+    // With no messages left it's a no-op
+    db.messages().delete_last_turn(&session.id).await.unwrap();
+}
+
 #[tokio::test]
 async fn test_message_update() {
     let (db, _tmp) = test_db().await;
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::permission::{PermissionDecision, PermissionRequest, PermissionService};
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::process::Command;
@@ -62,12 +62,60 @@ impl BashTool {
         let lower = command.to_lowercase();
         DANGEROUS_PATTERNS.iter().any(|pat| lower.contains(pat))
     }
+
+    /// Best-effort list of files the command writes, from redirection
+    /// targets and the arguments of common mutating commands. Shell
+    /// commands can touch anything, so this is a hint for the UI, not a
+    /// guarantee
+    fn affected_paths(command: &str) -> Vec<String> {
+        let mut paths = Vec::new();
+        for segment in command.split([';', '|']).flat_map(|s| s.split("&&")) {
+            let tokens: Vec<&str> = segment.split_whitespace().collect();
+            let mut i = 0;
+            while i < tokens.len() {
+                let tok = tokens[i];
+                if tok == ">" || tok == ">>" {
+                    if let Some(target) = tokens.get(i + 1) {
+                        paths.push(target.to_string());
+                    }
+                    i += 2;
+                    continue;
+                }
+                if let Some(target) = tok.strip_prefix('>').filter(|t| !t.is_empty()) {
+                    paths.push(target.trim_start_matches('>').to_string());
+                }
+                i += 1;
+            }
+
+            let args: Vec<&str> = tokens
+                .iter()
+                .skip(1)
+                .filter(|t| !t.starts_with('-') && !t.starts_with('>'))
+                .copied()
+                .collect();
+            match tokens.first().copied() {
+                Some("touch") | Some("rm") | Some("mkdir") | Some("unlink") => {
+                    paths.extend(args.iter().map(|a| a.to_string()));
+                }
+                // Destination is the last path argument
+                Some("mv") | Some("cp") => {
+                    if let Some(dest) = args.last() {
+                        paths.push(dest.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        paths.retain(|p| !p.starts_with("/dev/"));
+        paths.dedup();
+        paths
+    }
 }
 
 #[async_trait]
 impl Tool for BashTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "command".into(),
             ParamSchema {
@@ -137,17 +185,20 @@ impl Tool for BashTool {
             }
         }
 
-        let output = tokio::time::timeout(
-            Duration::from_secs(timeout),
-            Command::new("bash")
-                .arg("-c")
-                .arg(command)
-                .current_dir(&ctx.working_dir)
-                .output(),
-        )
-        .await
-        .map_err(|_| ToolError::Timeout(timeout))?
-        .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+        // kill_on_drop ensures the subprocess dies when the future is
+        // abandoned on timeout or cancellation
+        let mut cmd = Command::new("bash");
+        cmd.arg("-c")
+            .arg(command)
+            .current_dir(&ctx.working_dir)
+            .kill_on_drop(true);
+
+        let output = tokio::select! {
+            res = tokio::time::timeout(Duration::from_secs(timeout), cmd.output()) => res
+                .map_err(|_| ToolError::Timeout(timeout))?
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?,
+            _ = ctx.cancel_token.cancelled() => return Err(ToolError::Cancelled),
+        };
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -174,7 +225,7 @@ impl Tool for BashTool {
         }
 
         if output.status.success() {
-            Ok(ToolResult::success(result))
+            Ok(ToolResult::success(result).with_affected_paths(Self::affected_paths(command)))
         } else {
             let code = output.status.code().unwrap_or(-1);
             Ok(ToolResult::error(format!(
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::tool::*;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
@@ -39,8 +39,9 @@ struct SessionResponse {
 }
 
 impl CoderlmTool {
-    pub fn new(server_url: String) -> Self {
-        let client = reqwest::Client::builder()
+    pub fn new(server_url: String, http: &crate::core::config::HttpConfig) -> Self {
+        let client = http
+            .client_builder()
             .timeout(Duration::from_secs(30))
             .build()
             .unwrap_or_default();
@@ -174,7 +175,7 @@ impl CoderlmTool {
 #[async_trait]
 impl Tool for CoderlmTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
 
         params.insert(
             "operation".into(),
@@ -311,117 +312,125 @@ impl Tool for CoderlmTool {
 
         let working_dir = ctx.working_dir.to_string_lossy().to_string();
 
-        let result = match operation {
-            "health" => {
-                self.api_get("/health", &[], &working_dir).await
-            }
-            "structure" => {
-                let mut query = vec![];
-                if let Some(d) = params["depth"].as_u64() {
-                    query.push(("depth".to_string(), d.to_string()));
-                }
-                self.api_get("/structure", &query, &working_dir).await
-            }
-            "symbols" => {
-                let mut query = vec![];
-                if let Some(kind) = params["kind"].as_str() {
-                    query.push(("kind".to_string(), kind.to_string()));
-                }
-                if let Some(file) = params["file"].as_str() {
-                    query.push(("file".to_string(), file.to_string()));
+        // Abort in-flight server requests promptly when the run is cancelled
+        let op = async {
+            Ok::<_, ToolError>(match operation {
+                "health" => {
+                    self.api_get("/health", &[], &working_dir).await
                 }
-                if let Some(limit) = params["limit"].as_u64() {
-                    query.push(("limit".to_string(), limit.to_string()));
+                "structure" => {
+                    let mut query = vec![];
+                    if let Some(d) = params["depth"].as_u64() {
+                        query.push(("depth".to_string(), d.to_string()));
+                    }
+                    self.api_get("/structure", &query, &working_dir).await
                 }
-                self.api_get("/symbols", &query, &working_dir).await
-            }
-            "search" => {
-                let q = params["query"]
-                    .as_str()
-                    .ok_or_else(|| ToolError::InvalidParams("'search' requires 'query'".into()))?;
-                let mut query = vec![("query".to_string(), q.to_string())];
-                if let Some(limit) = params["limit"].as_u64() {
-                    query.push(("limit".to_string(), limit.to_string()));
-                }
-                self.api_get("/symbols/search", &query, &working_dir).await
-            }
-            "implementation" => {
-                let symbol = params["symbol"]
-                    .as_str()
-                    .ok_or_else(|| ToolError::InvalidParams("'implementation' requires 'symbol'".into()))?;
-                let mut query = vec![("symbol".to_string(), symbol.to_string())];
-                if let Some(file) = params["file"].as_str() {
-                    query.push(("file".to_string(), file.to_string()));
+                "symbols" => {
+                    let mut query = vec![];
+                    if let Some(kind) = params["kind"].as_str() {
+                        query.push(("kind".to_string(), kind.to_string()));
+                    }
+                    if let Some(file) = params["file"].as_str() {
+                        query.push(("file".to_string(), file.to_string()));
+                    }
+                    if let Some(limit) = params["limit"].as_u64() {
+                        query.push(("limit".to_string(), limit.to_string()));
+                    }
+                    self.api_get("/symbols", &query, &working_dir).await
                 }
-                self.api_get("/symbols/implementation", &query, &working_dir).await
-            }
-            "callers" => {
-                let symbol = params["symbol"]
-                    .as_str()
-                    .ok_or_else(|| ToolError::InvalidParams("'callers' requires 'symbol'".into()))?;
-                let mut query = vec![("symbol".to_string(), symbol.to_string())];
-                if let Some(file) = params["file"].as_str() {
-                    query.push(("file".to_string(), file.to_string()));
+                "search" => {
+                    let q = params["query"]
+                        .as_str()
+                        .ok_or_else(|| ToolError::InvalidParams("'search' requires 'query'".into()))?;
+                    let mut query = vec![("query".to_string(), q.to_string())];
+                    if let Some(limit) = params["limit"].as_u64() {
+                        query.push(("limit".to_string(), limit.to_string()));
+                    }
+                    self.api_get("/symbols/search", &query, &working_dir).await
                 }
-                if let Some(limit) = params["limit"].as_u64() {
-                    query.push(("limit".to_string(), limit.to_string()));
+                "implementation" => {
+                    let symbol = params["symbol"]
+                        .as_str()
+                        .ok_or_else(|| ToolError::InvalidParams("'implementation' requires 'symbol'".into()))?;
+                    let mut query = vec![("symbol".to_string(), symbol.to_string())];
+                    if let Some(file) = params["file"].as_str() {
+                        query.push(("file".to_string(), file.to_string()));
+                    }
+                    self.api_get("/symbols/implementation", &query, &working_dir).await
                 }
-                self.api_get("/symbols/callers", &query, &working_dir).await
-            }
-            "tests" => {
-                let symbol = params["symbol"]
-                    .as_str()
-                    .ok_or_else(|| ToolError::InvalidParams("'tests' requires 'symbol'".into()))?;
-                let mut query = vec![("symbol".to_string(), symbol.to_string())];
-                if let Some(file) = params["file"].as_str() {
-                    query.push(("file".to_string(), file.to_string()));
+                "callers" => {
+                    let symbol = params["symbol"]
+                        .as_str()
+                        .ok_or_else(|| ToolError::InvalidParams("'callers' requires 'symbol'".into()))?;
+                    let mut query = vec![("symbol".to_string(), symbol.to_string())];
+                    if let Some(file) = params["file"].as_str() {
+                        query.push(("file".to_string(), file.to_string()));
+                    }
+                    if let Some(limit) = params["limit"].as_u64() {
+                        query.push(("limit".to_string(), limit.to_string()));
+                    }
+                    self.api_get("/symbols/callers", &query, &working_dir).await
                 }
-                if let Some(limit) = params["limit"].as_u64() {
-                    query.push(("limit".to_string(), limit.to_string()));
+                "tests" => {
+                    let symbol = params["symbol"]
+                        .as_str()
+                        .ok_or_else(|| ToolError::InvalidParams("'tests' requires 'symbol'".into()))?;
+                    let mut query = vec![("symbol".to_string(), symbol.to_string())];
+                    if let Some(file) = params["file"].as_str() {
+                        query.push(("file".to_string(), file.to_string()));
+                    }
+                    if let Some(limit) = params["limit"].as_u64() {
+                        query.push(("limit".to_string(), limit.to_string()));
+                    }
+                    self.api_get("/symbols/tests", &query, &working_dir).await
                 }
-                self.api_get("/symbols/tests", &query, &working_dir).await
-            }
-            "variables" => {
-                let function = params["function"]
-                    .as_str()
-                    .ok_or_else(|| ToolError::InvalidParams("'variables' requires 'function'".into()))?;
-                let mut query = vec![("function".to_string(), function.to_string())];
-                if let Some(file) = params["file"].as_str() {
-                    query.push(("file".to_string(), file.to_string()));
+                "variables" => {
+                    let function = params["function"]
+                        .as_str()
+                        .ok_or_else(|| ToolError::InvalidParams("'variables' requires 'function'".into()))?;
+                    let mut query = vec![("function".to_string(), function.to_string())];
+                    if let Some(file) = params["file"].as_str() {
+                        query.push(("file".to_string(), file.to_string()));
+                    }
+                    self.api_get("/symbols/variables", &query, &working_dir).await
                 }
-                self.api_get("/symbols/variables", &query, &working_dir).await
-            }
-            "peek" => {
-                let file = params["file"]
-                    .as_str()
-                    .ok_or_else(|| ToolError::InvalidParams("'peek' requires 'file'".into()))?;
-                let start = params["start"]
-                    .as_u64()
-                    .ok_or_else(|| ToolError::InvalidParams("'peek' requires 'start'".into()))?;
-                let end = params["end"]
-                    .as_u64()
-                    .ok_or_else(|| ToolError::InvalidParams("'peek' requires 'end'".into()))?;
-                let query = vec![
-                    ("file".to_string(), file.to_string()),
-                    ("start".to_string(), start.to_string()),
-                    ("end".to_string(), end.to_string()),
-                ];
-                self.api_get("/peek", &query, &working_dir).await
-            }
-            "grep" => {
-                let pattern = params["pattern"]
-                    .as_str()
-                    .ok_or_else(|| ToolError::InvalidParams("'grep' requires 'pattern'".into()))?;
-                let mut query = vec![("pattern".to_string(), pattern.to_string())];
-                if let Some(max) = params["max_matches"].as_u64() {
-                    query.push(("max_matches".to_string(), max.to_string()));
+                "peek" => {
+                    let file = params["file"]
+                        .as_str()
+                        .ok_or_else(|| ToolError::InvalidParams("'peek' requires 'file'".into()))?;
+                    let start = params["start"]
+                        .as_u64()
+                        .ok_or_else(|| ToolError::InvalidParams("'peek' requires 'start'".into()))?;
+                    let end = params["end"]
+                        .as_u64()
+                        .ok_or_else(|| ToolError::InvalidParams("'peek' requires 'end'".into()))?;
+                    let query = vec![
+                        ("file".to_string(), file.to_string()),
+                        ("start".to_string(), start.to_string()),
+                        ("end".to_string(), end.to_string()),
+                    ];
+                    self.api_get("/peek", &query, &working_dir).await
                 }
-                if let Some(ctx_lines) = params["context_lines"].as_u64() {
-                    query.push(("context_lines".to_string(), ctx_lines.to_string()));
+                "grep" => {
+                    let pattern = params["pattern"]
+                        .as_str()
+                        .ok_or_else(|| ToolError::InvalidParams("'grep' requires 'pattern'".into()))?;
+                    let mut query = vec![("pattern".to_string(), pattern.to_string())];
+                    if let Some(max) = params["max_matches"].as_u64() {
+                        query.push(("max_matches".to_string(), max.to_string()));
+                    }
+                    if let Some(ctx_lines) = params["context_lines"].as_u64() {
+                        query.push(("context_lines".to_string(), ctx_lines.to_string()));
+                    }
+                    self.api_get("/grep", &query, &working_dir).await
                 }
-                self.api_get("/grep", &query, &working_dir).await
-            }
-            _ => unreachable!(),
+                _ => unreachable!(),
+            })
+        };
+
+        let result = tokio::select! {
+            res = op => res?,
+            _ = ctx.cancel_token.cancelled() => return Err(ToolError::Cancelled),
         };
 
         match result {
@@ -429,4 +438,30 @@ impl Tool for CoderlmTool {
             Err(msg) => Ok(ToolResult::error(msg)),
         }
     }
+
+    async fn on_files_changed(&self, paths: &[String], _ctx: &ToolContext) {
+        // Without an active session there's no stale index to refresh
+        let session_id = {
+            let guard = self.session.read().await;
+            match guard.as_ref() {
+                Some(state) => state.session_id.clone(),
+                None => return,
+            }
+        };
+
+        let resp = self
+            .client
+            .post(format!("{}/invalidate", self.server_url))
+            .header("X-Session-Id", &session_id)
+            .json(&serde_json::json!({ "paths": paths }))
+            .send()
+            .await;
+
+        match resp {
+            Ok(r) if r.status().is_success() => {}
+            // Endpoint unsupported (or request failed): drop the cached
+            // session so the next operation forces a full re-scan
+            _ => self.invalidate_session().await,
+        }
+    }
 }
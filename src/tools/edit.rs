@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::permission::{PermissionDecision, PermissionRequest, PermissionService};
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 pub struct EditTool {
@@ -18,7 +18,7 @@ impl EditTool {
 #[async_trait]
 impl Tool for EditTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "path".into(),
             ParamSchema {
@@ -43,11 +43,22 @@ impl Tool for EditTool {
                 enum_values: None,
             },
         );
+        params.insert(
+            "replace_all".into(),
+            ParamSchema {
+                param_type: "boolean".into(),
+                description: "Replace every occurrence of old_string instead of \
+                    requiring a unique match (default false)"
+                    .into(),
+                enum_values: None,
+            },
+        );
 
         ToolDefinition {
             name: "edit".into(),
             description: "Edit a file by replacing an exact string match with new content. \
-                The old_string must uniquely match one location in the file."
+                The old_string must uniquely match one location in the file, unless \
+                replace_all is set."
                 .into(),
             parameters: params,
             required: vec!["path".into(), "old_string".into(), "new_string".into()],
@@ -67,6 +78,7 @@ impl Tool for EditTool {
         let new_string = params["new_string"]
             .as_str()
             .ok_or_else(|| ToolError::InvalidParams("missing 'new_string'".into()))?;
+        let replace_all = params["replace_all"].as_bool().unwrap_or(false);
 
         let path = if std::path::Path::new(path_str).is_absolute() {
             std::path::PathBuf::from(path_str)
@@ -90,9 +102,10 @@ impl Tool for EditTool {
             ));
         }
 
-        if matches.len() > 1 {
+        if matches.len() > 1 && !replace_all {
             return Ok(ToolResult::error(format!(
-                "old_string found {} times. It must be unique. Add more context.",
+                "old_string found {} times. It must be unique. Add more context, \
+                or set replace_all to change every occurrence.",
                 matches.len()
             )));
         }
@@ -115,16 +128,33 @@ impl Tool for EditTool {
             }
         }
 
-        let new_content = content.replacen(old_string, new_string, 1);
+        let new_content = if replace_all {
+            content.replace(old_string, new_string)
+        } else {
+            content.replacen(old_string, new_string, 1)
+        };
         tokio::fs::write(&path, &new_content)
             .await
             .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
 
-        Ok(ToolResult::success(format!(
-            "Edited {}. Replaced {} chars with {} chars.",
-            path.display(),
-            old_string.len(),
-            new_string.len()
-        )))
+        let mut result = ToolResult::success(if replace_all {
+            format!(
+                "Edited {}. Replaced {} occurrence(s).",
+                path.display(),
+                matches.len()
+            )
+        } else {
+            format!(
+                "Edited {}. Replaced {} chars with {} chars.",
+                path.display(),
+                old_string.len(),
+                new_string.len()
+            )
+        });
+        result.metadata = Some(serde_json::json!({
+            "path": path.to_string_lossy(),
+            "diff": crate::core::diff::unified_diff(&content, &new_content),
+        }));
+        Ok(result.with_affected_paths(vec![path.to_string_lossy().to_string()]))
     }
 }
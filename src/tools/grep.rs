@@ -1,14 +1,14 @@
 use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct GrepTool;
 
 #[async_trait]
 impl Tool for GrepTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "pattern".into(),
             ParamSchema {
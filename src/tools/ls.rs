@@ -1,14 +1,14 @@
 use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct LsTool;
 
 #[async_trait]
 impl Tool for LsTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "path".into(),
             ParamSchema {
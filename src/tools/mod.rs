@@ -4,7 +4,10 @@ mod edit;
 mod glob_tool;
 mod grep;
 mod ls;
+mod multi_edit;
+mod scratchpad;
 mod send_message;
+mod session_changes;
 mod task_mgmt;
 mod team;
 mod view;
@@ -16,7 +19,10 @@ pub use edit::EditTool;
 pub use glob_tool::GlobTool;
 pub use grep::GrepTool;
 pub use ls::LsTool;
+pub use multi_edit::MultiEditTool;
+pub use scratchpad::ScratchpadTool;
 pub use send_message::{CheckInboxTool, SendMessageTool};
+pub use session_changes::SessionChangesTool;
 pub use task_mgmt::{TaskCreateTool, TaskGetTool, TaskListTool, TaskUpdateTool};
 pub use team::{SpawnAgentTool, TeamCreateTool, TeamDeleteTool};
 pub use view::ViewTool;
@@ -33,6 +39,7 @@ mod tests;
 pub async fn create_all_tools(
     permission_service: Arc<dyn PermissionService>,
     coderlm_server_url: String,
+    http: &crate::core::config::HttpConfig,
     team_state: Arc<RwLock<Option<TeamState>>>,
 ) -> Vec<Arc<dyn Tool>> {
     let mut tools: Vec<Arc<dyn Tool>> = vec![
@@ -40,15 +47,18 @@ pub async fn create_all_tools(
         Arc::new(ViewTool),
         Arc::new(WriteTool::new(permission_service.clone())),
         Arc::new(EditTool::new(permission_service.clone())),
+        Arc::new(MultiEditTool::new(permission_service.clone())),
         Arc::new(LsTool),
         Arc::new(GlobTool),
         Arc::new(GrepTool),
+        Arc::new(ScratchpadTool::new()),
+        Arc::new(SessionChangesTool::new()),
     ];
 
     // Only add CodeRLM if server is reachable
-    if is_coderlm_available(&coderlm_server_url).await {
+    if is_coderlm_available(&coderlm_server_url, http).await {
         eprintln!("  \x1b[32m✓\x1b[0m CodeRLM connected");
-        tools.push(Arc::new(CoderlmTool::new(coderlm_server_url)));
+        tools.push(Arc::new(CoderlmTool::new(coderlm_server_url, http)));
     }
 
     // Team tools
@@ -65,8 +75,12 @@ pub async fn create_all_tools(
     tools
 }
 
-async fn is_coderlm_available(server_url: &str) -> bool {
-    let client = reqwest::Client::builder()
+pub async fn is_coderlm_available(
+    server_url: &str,
+    http: &crate::core::config::HttpConfig,
+) -> bool {
+    let client = http
+        .client_builder()
         .timeout(std::time::Duration::from_secs(2))
         .build()
         .unwrap_or_default();
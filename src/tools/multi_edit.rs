@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use crate::core::error::ToolError;
+use crate::core::permission::{PermissionDecision, PermissionRequest, PermissionService};
+use crate::core::tool::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub struct MultiEditTool {
+    permission_service: Arc<dyn PermissionService>,
+}
+
+impl MultiEditTool {
+    pub fn new(permission_service: Arc<dyn PermissionService>) -> Self {
+        Self { permission_service }
+    }
+}
+
+#[async_trait]
+impl Tool for MultiEditTool {
+    fn definition(&self) -> ToolDefinition {
+        let mut params = BTreeMap::new();
+        params.insert(
+            "path".into(),
+            ParamSchema {
+                param_type: "string".into(),
+                description: "File path to edit".into(),
+                enum_values: None,
+            },
+        );
+        params.insert(
+            "edits".into(),
+            ParamSchema {
+                param_type: "array".into(),
+                description: "Array of {old_string, new_string, replace_all?} objects, \
+                    applied in order. Each old_string must match the buffer as left by \
+                    the previous edits; set replace_all to true to replace every \
+                    occurrence instead of requiring a unique match."
+                    .into(),
+                enum_values: None,
+            },
+        );
+
+        ToolDefinition {
+            name: "multi_edit".into(),
+            description: "Apply several string replacements to one file atomically. \
+                All edits are validated against an in-memory buffer and the file is \
+                only written if every edit succeeds; on failure nothing changes on disk."
+                .into(),
+            parameters: params,
+            required: vec!["path".into(), "edits".into()],
+        }
+    }
+
+    async fn run(&self, call: &ToolCall, ctx: &ToolContext) -> Result<ToolResult, ToolError> {
+        let params: serde_json::Value =
+            serde_json::from_str(&call.input).map_err(|e| ToolError::InvalidParams(e.to_string()))?;
+
+        let path_str = params["path"]
+            .as_str()
+            .ok_or_else(|| ToolError::InvalidParams("missing 'path'".into()))?;
+        let edits = params["edits"]
+            .as_array()
+            .ok_or_else(|| ToolError::InvalidParams("missing 'edits' array".into()))?;
+
+        if edits.is_empty() {
+            return Ok(ToolResult::error("'edits' array is empty.".into()));
+        }
+
+        let path = if std::path::Path::new(path_str).is_absolute() {
+            std::path::PathBuf::from(path_str)
+        } else {
+            ctx.working_dir.join(path_str)
+        };
+
+        if !path.exists() {
+            return Ok(ToolResult::error(format!("File not found: {}", path.display())));
+        }
+
+        let original = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+
+        // Apply every edit against the in-memory buffer first, so a
+        // mid-sequence failure never leaves the file half-edited
+        let mut buffer = original.clone();
+        for (i, edit) in edits.iter().enumerate() {
+            let old_string = edit["old_string"].as_str().ok_or_else(|| {
+                ToolError::InvalidParams(format!("edit {} is missing 'old_string'", i + 1))
+            })?;
+            let new_string = edit["new_string"].as_str().ok_or_else(|| {
+                ToolError::InvalidParams(format!("edit {} is missing 'new_string'", i + 1))
+            })?;
+            let replace_all = edit["replace_all"].as_bool().unwrap_or(false);
+
+            let count = buffer.matches(old_string).count();
+            if count == 0 {
+                return Ok(ToolResult::error(format!(
+                    "Edit {} of {} failed: old_string not found. File unchanged.",
+                    i + 1,
+                    edits.len()
+                )));
+            }
+            if count > 1 && !replace_all {
+                return Ok(ToolResult::error(format!(
+                    "Edit {} of {} failed: old_string found {} times. It must be \
+                    unique, or set replace_all. File unchanged.",
+                    i + 1,
+                    edits.len(),
+                    count
+                )));
+            }
+
+            buffer = if replace_all {
+                buffer.replace(old_string, new_string)
+            } else {
+                buffer.replacen(old_string, new_string, 1)
+            };
+        }
+
+        let req = PermissionRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: ctx.session_id.clone(),
+            tool_name: "multi_edit".into(),
+            action: "edit".into(),
+            description: format!("Apply {} edits to: {}", edits.len(), path.display()),
+            path: Some(path.to_string_lossy().to_string()),
+        };
+        match self.permission_service.request(req).await {
+            PermissionDecision::Allow | PermissionDecision::AllowPersistent => {}
+            PermissionDecision::Deny => {
+                return Err(ToolError::PermissionDenied {
+                    tool: "multi_edit".into(),
+                    action: path_str.into(),
+                });
+            }
+        }
+
+        tokio::fs::write(&path, &buffer)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+
+        let mut result = ToolResult::success(format!(
+            "Applied {} edits to {}.",
+            edits.len(),
+            path.display()
+        ));
+        result.metadata = Some(serde_json::json!({
+            "path": path.to_string_lossy(),
+            "diff": crate::core::diff::unified_diff(&original, &buffer),
+        }));
+        Ok(result.with_affected_paths(vec![path.to_string_lossy().to_string()]))
+    }
+}
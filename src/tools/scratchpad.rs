@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use crate::core::error::ToolError;
+use crate::core::tool::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// In-memory scratchpad for stashing intermediate results between turns
+/// without polluting the conversation or creating files. Entries are
+/// scoped per session via `ctx.session_id` and live for the process.
+pub struct ScratchpadTool {
+    /// session_id -> key -> value
+    store: Arc<Mutex<HashMap<String, HashMap<String, String>>>>,
+}
+
+impl ScratchpadTool {
+    pub fn new() -> Self {
+        Self {
+            store: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for ScratchpadTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for ScratchpadTool {
+    fn definition(&self) -> ToolDefinition {
+        let mut params = BTreeMap::new();
+        params.insert(
+            "action".into(),
+            ParamSchema {
+                param_type: "string".into(),
+                description: "Operation to perform".into(),
+                enum_values: Some(vec![
+                    "set".into(),
+                    "get".into(),
+                    "append".into(),
+                    "clear".into(),
+                    "list".into(),
+                ]),
+            },
+        );
+        params.insert(
+            "key".into(),
+            ParamSchema {
+                param_type: "string".into(),
+                description: "Scratchpad key (required for set/get/append/clear)".into(),
+                enum_values: None,
+            },
+        );
+        params.insert(
+            "value".into(),
+            ParamSchema {
+                param_type: "string".into(),
+                description: "Value to store (required for set/append)".into(),
+                enum_values: None,
+            },
+        );
+
+        ToolDefinition {
+            name: "scratchpad".into(),
+            description: "Session-scoped scratchpad for intermediate results. Store notes, \
+                lists or partial output under a key and retrieve them in a later turn \
+                instead of re-deriving them."
+                .into(),
+            parameters: params,
+            required: vec!["action".into()],
+        }
+    }
+
+    async fn run(&self, call: &ToolCall, ctx: &ToolContext) -> Result<ToolResult, ToolError> {
+        let params: serde_json::Value =
+            serde_json::from_str(&call.input).unwrap_or(serde_json::json!({}));
+
+        let action = params["action"]
+            .as_str()
+            .ok_or_else(|| ToolError::InvalidParams("'action' is required".into()))?;
+
+        let mut store = self
+            .store
+            .lock()
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+        let session = store.entry(ctx.session_id.clone()).or_default();
+
+        let key = params["key"].as_str();
+
+        match action {
+            "set" => {
+                let key = key
+                    .ok_or_else(|| ToolError::InvalidParams("'key' is required for set".into()))?;
+                let value = params["value"].as_str().ok_or_else(|| {
+                    ToolError::InvalidParams("'value' is required for set".into())
+                })?;
+                session.insert(key.to_string(), value.to_string());
+                Ok(ToolResult::success(format!("Stored '{key}' ({} chars)", value.len())))
+            }
+            "get" => {
+                let key = key
+                    .ok_or_else(|| ToolError::InvalidParams("'key' is required for get".into()))?;
+                match session.get(key) {
+                    Some(value) => Ok(ToolResult::success(value.clone())),
+                    None => Ok(ToolResult::error(format!("No scratchpad entry for '{key}'"))),
+                }
+            }
+            "append" => {
+                let key = key.ok_or_else(|| {
+                    ToolError::InvalidParams("'key' is required for append".into())
+                })?;
+                let value = params["value"].as_str().ok_or_else(|| {
+                    ToolError::InvalidParams("'value' is required for append".into())
+                })?;
+                let entry = session.entry(key.to_string()).or_default();
+                if !entry.is_empty() {
+                    entry.push('\n');
+                }
+                entry.push_str(value);
+                Ok(ToolResult::success(format!(
+                    "Appended to '{key}' (now {} chars)",
+                    entry.len()
+                )))
+            }
+            "clear" => {
+                let key = key.ok_or_else(|| {
+                    ToolError::InvalidParams("'key' is required for clear".into())
+                })?;
+                match session.remove(key) {
+                    Some(_) => Ok(ToolResult::success(format!("Cleared '{key}'"))),
+                    None => Ok(ToolResult::error(format!("No scratchpad entry for '{key}'"))),
+                }
+            }
+            "list" => {
+                if session.is_empty() {
+                    return Ok(ToolResult::success("Scratchpad is empty.".into()));
+                }
+                let mut keys: Vec<String> = session
+                    .iter()
+                    .map(|(k, v)| format!("  {k}  ({} chars)", v.len()))
+                    .collect();
+                keys.sort();
+                Ok(ToolResult::success(format!("Scratchpad keys:\n{}", keys.join("\n"))))
+            }
+            _ => Err(ToolError::InvalidParams(format!(
+                "Unknown action '{action}'. Use set, get, append, clear or list."
+            ))),
+        }
+    }
+}
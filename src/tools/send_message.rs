@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::team::{self, InboxMessage, TeamState};
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock};
 
 pub struct SendMessageTool {
@@ -25,7 +25,7 @@ impl SendMessageTool {
 #[async_trait]
 impl Tool for SendMessageTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "type".into(),
             ParamSchema {
@@ -249,7 +249,7 @@ impl CheckInboxTool {
 #[async_trait]
 impl Tool for CheckInboxTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "wait_seconds".into(),
             ParamSchema {
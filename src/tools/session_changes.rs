@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use crate::core::error::ToolError;
+use crate::core::tool::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Summarizes everything the session changed so far: one line per file
+/// with its status (created/modified/deleted) and line counts diffed
+/// against git HEAD. Paths are collected through the
+/// [`on_files_changed`](Tool::on_files_changed) hook the agent fires
+/// after successful edit/write calls, so the model can call this at the
+/// end of a task to write an accurate PR description.
+pub struct SessionChangesTool {
+    /// session_id -> paths changed in that session
+    changed: Arc<Mutex<HashMap<String, BTreeSet<String>>>>,
+}
+
+impl SessionChangesTool {
+    pub fn new() -> Self {
+        Self {
+            changed: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for SessionChangesTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for SessionChangesTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "session_changes".into(),
+            description: "Summarize all files changed in this session: per-file status \
+                (created/modified/deleted) and lines added/removed, diffed against git HEAD. \
+                Call this when wrapping up a task to write an accurate summary or PR \
+                description."
+                .into(),
+            parameters: BTreeMap::new(),
+            required: vec![],
+        }
+    }
+
+    async fn run(&self, _call: &ToolCall, ctx: &ToolContext) -> Result<ToolResult, ToolError> {
+        let paths: Vec<String> = {
+            let changed = self
+                .changed
+                .lock()
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            changed
+                .get(&ctx.session_id)
+                .map(|set| set.iter().cloned().collect())
+                .unwrap_or_default()
+        };
+
+        if paths.is_empty() {
+            return Ok(ToolResult::success(
+                "No files have been changed in this session.".into(),
+            ));
+        }
+
+        let op = async {
+            let mut lines = Vec::new();
+            let mut total_added = 0u64;
+            let mut total_removed = 0u64;
+            for path in &paths {
+                let (status, added, removed) = describe_change(path, ctx).await;
+                total_added += added.unwrap_or(0);
+                total_removed += removed.unwrap_or(0);
+                let counts = match (added, removed) {
+                    (Some(a), Some(r)) => format!(" (+{a}/-{r})"),
+                    _ => String::new(),
+                };
+                lines.push(format!("  {status:<9} {path}{counts}"));
+            }
+            format!(
+                "{} file(s) changed this session (+{total_added}/-{total_removed}):\n{}",
+                paths.len(),
+                lines.join("\n")
+            )
+        };
+
+        let summary = tokio::select! {
+            s = op => s,
+            _ = ctx.cancel_token.cancelled() => return Err(ToolError::Cancelled),
+        };
+
+        Ok(ToolResult::success(summary))
+    }
+
+    async fn on_files_changed(&self, paths: &[String], ctx: &ToolContext) {
+        if let Ok(mut changed) = self.changed.lock() {
+            let session = changed.entry(ctx.session_id.clone()).or_default();
+            for path in paths {
+                session.insert(path.clone());
+            }
+        }
+    }
+}
+
+/// Classify one path and count its added/removed lines against HEAD.
+/// Falls back to filesystem-only information when git is unavailable.
+async fn describe_change(path: &str, ctx: &ToolContext) -> (&'static str, Option<u64>, Option<u64>) {
+    let abs = if std::path::Path::new(path).is_absolute() {
+        std::path::PathBuf::from(path)
+    } else {
+        ctx.working_dir.join(path)
+    };
+
+    let porcelain = git_output(&["status", "--porcelain", "--", path], ctx).await;
+    let status = match porcelain.as_deref() {
+        Some(out) if out.starts_with("??") => "created",
+        Some(out) if out.contains('D') => "deleted",
+        Some(out) if !out.is_empty() => "modified",
+        Some(_) => {
+            // Clean in git but touched this session: committed or reverted
+            if abs.exists() {
+                "modified"
+            } else {
+                "deleted"
+            }
+        }
+        None => {
+            if abs.exists() {
+                "modified"
+            } else {
+                "deleted"
+            }
+        }
+    };
+
+    if status == "created" {
+        // Untracked files have no diff against HEAD; count their lines
+        let added = tokio::fs::read_to_string(&abs)
+            .await
+            .map(|c| c.lines().count() as u64)
+            .ok();
+        return (status, added, Some(0));
+    }
+
+    let numstat = git_output(&["diff", "--numstat", "HEAD", "--", path], ctx).await;
+    let counts = numstat.as_deref().and_then(|out| {
+        let mut fields = out.split_whitespace();
+        // Binary files report "-" for both counts
+        let added = fields.next()?.parse().ok()?;
+        let removed = fields.next()?.parse().ok()?;
+        Some((added, removed))
+    });
+    match counts {
+        Some((a, r)) => (status, Some(a), Some(r)),
+        None => (status, None, None),
+    }
+}
+
+async fn git_output(args: &[&str], ctx: &ToolContext) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(&ctx.working_dir)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
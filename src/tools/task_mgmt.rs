@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::team::{self, TaskItem, TaskStatus, TeamState};
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
@@ -31,7 +31,7 @@ impl TaskCreateTool {
 #[async_trait]
 impl Tool for TaskCreateTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "subject".into(),
             ParamSchema {
@@ -124,7 +124,7 @@ impl TaskGetTool {
 #[async_trait]
 impl Tool for TaskGetTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "task_id".into(),
             ParamSchema {
@@ -177,7 +177,7 @@ impl TaskUpdateTool {
 #[async_trait]
 impl Tool for TaskUpdateTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "task_id".into(),
             ParamSchema {
@@ -362,7 +362,7 @@ impl Tool for TaskListTool {
         ToolDefinition {
             name: "task_list".into(),
             description: "List all tasks in the team's shared task list.".into(),
-            parameters: HashMap::new(),
+            parameters: BTreeMap::new(),
             required: vec![],
         }
     }
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::team::{self, TeamConfig, TeamMember, TeamState};
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
@@ -43,7 +43,7 @@ impl TeamCreateTool {
 #[async_trait]
 impl Tool for TeamCreateTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "team_name".into(),
             ParamSchema {
@@ -163,7 +163,7 @@ impl Tool for TeamDeleteTool {
         ToolDefinition {
             name: "team_delete".into(),
             description: "Delete the current team and clean up all team resources.".into(),
-            parameters: HashMap::new(),
+            parameters: BTreeMap::new(),
             required: vec![],
         }
     }
@@ -223,7 +223,7 @@ impl SpawnAgentTool {
 #[async_trait]
 impl Tool for SpawnAgentTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "name".into(),
             ParamSchema {
@@ -51,12 +51,17 @@ async fn test_tool_definitions() {
 
     let perm: Arc<dyn PermissionService> = Arc::new(MockPerm);
     let team_state = Arc::new(RwLock::new(None));
-    let tools = create_all_tools(perm, "http://127.0.0.1:19999".into(), team_state).await;
+    let tools = create_all_tools(
+        perm,
+        "http://127.0.0.1:19999".into(),
+        &crate::core::config::HttpConfig::default(),
+        team_state,
+    ).await;
 
-    // 16 tools without CodeRLM, 17 with CodeRLM server running
+    // 18 tools without CodeRLM, 19 with CodeRLM server running
     assert!(
-        tools.len() >= 16,
-        "Expected at least 16 tools, got {}",
+        tools.len() >= 18,
+        "Expected at least 18 tools, got {}",
         tools.len()
     );
 
@@ -65,9 +70,11 @@ async fn test_tool_definitions() {
     assert!(names.contains(&"view".to_string()));
     assert!(names.contains(&"write".to_string()));
     assert!(names.contains(&"edit".to_string()));
+    assert!(names.contains(&"multi_edit".to_string()));
     assert!(names.contains(&"ls".to_string()));
     assert!(names.contains(&"glob".to_string()));
     assert!(names.contains(&"grep".to_string()));
+    assert!(names.contains(&"scratchpad".to_string()));
     // coderlm is optional — only present if server is reachable
     // Team tools
     assert!(names.contains(&"team_create".to_string()));
@@ -94,7 +101,10 @@ async fn test_tool_definitions() {
 
 #[test]
 fn test_coderlm_tool_definition() {
-    let tool = super::CoderlmTool::new("http://127.0.0.1:9999".into());
+    let tool = super::CoderlmTool::new(
+        "http://127.0.0.1:9999".into(),
+        &crate::core::config::HttpConfig::default(),
+    );
     let def = tool.definition();
 
     assert_eq!(def.name, "coderlm");
@@ -121,7 +131,10 @@ fn test_coderlm_tool_definition() {
 #[tokio::test]
 async fn test_coderlm_server_unavailable() {
     // Use a port that is almost certainly not running CodeRLM
-    let tool = super::CoderlmTool::new("http://127.0.0.1:19999".into());
+    let tool = super::CoderlmTool::new(
+        "http://127.0.0.1:19999".into(),
+        &crate::core::config::HttpConfig::default(),
+    );
     let ctx = test_context(std::path::Path::new("/tmp"));
 
     let call = ToolCall {
@@ -138,7 +151,10 @@ async fn test_coderlm_server_unavailable() {
 
 #[tokio::test]
 async fn test_coderlm_invalid_operation() {
-    let tool = super::CoderlmTool::new("http://127.0.0.1:19999".into());
+    let tool = super::CoderlmTool::new(
+        "http://127.0.0.1:19999".into(),
+        &crate::core::config::HttpConfig::default(),
+    );
     let ctx = test_context(std::path::Path::new("/tmp"));
 
     let call = ToolCall {
@@ -154,7 +170,10 @@ async fn test_coderlm_invalid_operation() {
 
 #[tokio::test]
 async fn test_coderlm_missing_required_params() {
-    let tool = super::CoderlmTool::new("http://127.0.0.1:19999".into());
+    let tool = super::CoderlmTool::new(
+        "http://127.0.0.1:19999".into(),
+        &crate::core::config::HttpConfig::default(),
+    );
     let ctx = test_context(std::path::Path::new("/tmp"));
 
     // 'search' requires 'query' parameter
@@ -224,6 +243,86 @@ async fn test_view_tool_not_found() {
     assert!(result.content.contains("not found"));
 }
 
+#[tokio::test]
+async fn test_view_tool_truncates_huge_single_line() {
+    let tmp = tempfile::tempdir().unwrap();
+    let test_file = tmp.path().join("blob.min.js");
+    // One 1MB line followed by a normal one
+    let huge = "x".repeat(1_000_000);
+    std::fs::write(&test_file, format!("{huge}\nshort line\n")).unwrap();
+
+    let tool = super::ViewTool;
+    let ctx = test_context(tmp.path());
+
+    let call = ToolCall {
+        id: "1".into(),
+        name: "view".into(),
+        input: serde_json::json!({"path": "blob.min.js"}).to_string(),
+    };
+
+    let result = tool.run(&call, &ctx).await.unwrap();
+    assert!(!result.is_error);
+    // The pathological line can't dominate the output
+    assert!(result.content.len() < 10_000);
+    assert!(result.content.contains("[line truncated: 1000000 chars total]"));
+    assert!(result.content.contains("short line"));
+    assert!(result.content.contains("1 line(s) exceeded"));
+}
+
+#[tokio::test]
+async fn test_session_changes_tool() {
+    let tmp = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(tmp.path())
+            .output()
+            .unwrap()
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "test@test"]);
+    git(&["config", "user.name", "test"]);
+    std::fs::write(tmp.path().join("existing.rs"), "line one\nline two\n").unwrap();
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "init"]);
+
+    let tool = super::SessionChangesTool::new();
+    let ctx = test_context(tmp.path());
+
+    // Nothing recorded yet
+    let call = ToolCall {
+        id: "1".into(),
+        name: "session_changes".into(),
+        input: "{}".into(),
+    };
+    let result = tool.run(&call, &ctx).await.unwrap();
+    assert!(result.content.contains("No files have been changed"));
+
+    // Modify a tracked file and create a new one, then record both
+    std::fs::write(
+        tmp.path().join("existing.rs"),
+        "line one\nchanged\nadded\n",
+    )
+    .unwrap();
+    std::fs::write(tmp.path().join("new.rs"), "a\nb\nc\n").unwrap();
+    tool.on_files_changed(&["existing.rs".into(), "new.rs".into()], &ctx)
+        .await;
+
+    let result = tool.run(&call, &ctx).await.unwrap();
+    assert!(!result.is_error);
+    assert!(result.content.contains("2 file(s) changed"));
+    assert!(result.content.contains("modified  existing.rs (+2/-1)"));
+    assert!(result.content.contains("created   new.rs (+3/-0)"));
+
+    // Paths are scoped per session
+    let other_ctx = ToolContext {
+        session_id: "other-session".into(),
+        ..test_context(tmp.path())
+    };
+    let result = tool.run(&call, &other_ctx).await.unwrap();
+    assert!(result.content.contains("No files have been changed"));
+}
+
 #[tokio::test]
 async fn test_ls_tool() {
     let tmp = tempfile::tempdir().unwrap();
@@ -271,6 +370,51 @@ async fn test_glob_tool() {
     assert!(result.content.contains("2 files found"));
 }
 
+#[tokio::test]
+async fn test_scratchpad_tool() {
+    let tmp = tempfile::tempdir().unwrap();
+    let tool = super::ScratchpadTool::new();
+    let ctx = test_context(tmp.path());
+
+    let call = |action: &str, key: &str, value: Option<&str>| {
+        let mut input = serde_json::json!({"action": action, "key": key});
+        if let Some(v) = value {
+            input["value"] = serde_json::json!(v);
+        }
+        ToolCall {
+            id: "1".into(),
+            name: "scratchpad".into(),
+            input: input.to_string(),
+        }
+    };
+
+    // set + get round-trip
+    let result = tool.run(&call("set", "files", Some("a.rs")), &ctx).await.unwrap();
+    assert!(!result.is_error);
+    let result = tool.run(&call("get", "files", None), &ctx).await.unwrap();
+    assert_eq!(result.content, "a.rs");
+
+    // append joins with a newline
+    tool.run(&call("append", "files", Some("b.rs")), &ctx).await.unwrap();
+    let result = tool.run(&call("get", "files", None), &ctx).await.unwrap();
+    assert_eq!(result.content, "a.rs\nb.rs");
+
+    // entries are scoped to the session
+    let other_ctx = ToolContext {
+        session_id: "other-session".into(),
+        working_dir: tmp.path().to_path_buf(),
+        cancel_token: CancellationToken::new(),
+        team_state: Arc::new(RwLock::new(None)),
+    };
+    let result = tool.run(&call("get", "files", None), &other_ctx).await.unwrap();
+    assert!(result.is_error);
+
+    // clear removes the entry
+    tool.run(&call("clear", "files", None), &ctx).await.unwrap();
+    let result = tool.run(&call("get", "files", None), &ctx).await.unwrap();
+    assert!(result.is_error);
+}
+
 #[tokio::test]
 async fn test_write_and_edit_tools() {
     use crate::core::permission::{PermissionDecision, PermissionService};
@@ -372,6 +516,187 @@ async fn test_edit_tool_not_unique() {
     assert!(result.content.contains("3 times"));
 }
 
+#[tokio::test]
+async fn test_edit_tool_replace_all() {
+    use crate::core::permission::{PermissionDecision, PermissionService};
+    use std::sync::Arc;
+
+    struct AutoApprove;
+    #[async_trait::async_trait]
+    impl PermissionService for AutoApprove {
+        async fn request(
+            &self,
+            _req: crate::core::permission::PermissionRequest,
+        ) -> PermissionDecision {
+            PermissionDecision::Allow
+        }
+        fn auto_approve_session(&self, _session_id: &str) {}
+    }
+
+    let tmp = tempfile::tempdir().unwrap();
+    let perm: Arc<dyn PermissionService> = Arc::new(AutoApprove);
+    let ctx = test_context(tmp.path());
+
+    let file_path = tmp.path().join("rename.rs");
+    std::fs::write(&file_path, "let cnt = 0;\ncnt += 1;\nprintln!(\"{cnt}\");\n").unwrap();
+
+    let edit_tool = super::EditTool::new(perm);
+    let call = ToolCall {
+        id: "1".into(),
+        name: "edit".into(),
+        input: serde_json::json!({
+            "path": file_path.to_str().unwrap(),
+            "old_string": "cnt",
+            "new_string": "count",
+            "replace_all": true
+        })
+        .to_string(),
+    };
+
+    let result = edit_tool.run(&call, &ctx).await.unwrap();
+    assert!(!result.is_error);
+    assert!(result.content.contains("3 occurrence(s)"));
+
+    let content = std::fs::read_to_string(&file_path).unwrap();
+    assert_eq!(content, "let count = 0;\ncount += 1;\nprintln!(\"{count}\");\n");
+}
+
+#[tokio::test]
+async fn test_multi_edit_tool_applies_all_edits() {
+    use crate::core::permission::{PermissionDecision, PermissionService};
+    use std::sync::Arc;
+
+    struct AutoApprove;
+    #[async_trait::async_trait]
+    impl PermissionService for AutoApprove {
+        async fn request(
+            &self,
+            _req: crate::core::permission::PermissionRequest,
+        ) -> PermissionDecision {
+            PermissionDecision::Allow
+        }
+        fn auto_approve_session(&self, _session_id: &str) {}
+    }
+
+    let tmp = tempfile::tempdir().unwrap();
+    let perm: Arc<dyn PermissionService> = Arc::new(AutoApprove);
+    let ctx = test_context(tmp.path());
+
+    let file_path = tmp.path().join("multi.rs");
+    std::fs::write(&file_path, "fn one() {}\nfn two() {}\ncall();\ncall();\n").unwrap();
+
+    let tool = super::MultiEditTool::new(perm);
+    let call = ToolCall {
+        id: "1".into(),
+        name: "multi_edit".into(),
+        input: serde_json::json!({
+            "path": file_path.to_str().unwrap(),
+            "edits": [
+                {"old_string": "fn one", "new_string": "fn first"},
+                {"old_string": "fn two", "new_string": "fn second"},
+                {"old_string": "call();", "new_string": "invoke();", "replace_all": true}
+            ]
+        })
+        .to_string(),
+    };
+
+    let result = tool.run(&call, &ctx).await.unwrap();
+    assert!(!result.is_error);
+    assert!(result.content.contains("3 edits"));
+    assert_eq!(
+        result.affected_paths,
+        vec![file_path.to_string_lossy().to_string()]
+    );
+
+    let content = std::fs::read_to_string(&file_path).unwrap();
+    assert_eq!(content, "fn first() {}\nfn second() {}\ninvoke();\ninvoke();\n");
+}
+
+#[tokio::test]
+async fn test_multi_edit_tool_is_atomic() {
+    use crate::core::permission::{PermissionDecision, PermissionService};
+    use std::sync::Arc;
+
+    struct AutoApprove;
+    #[async_trait::async_trait]
+    impl PermissionService for AutoApprove {
+        async fn request(
+            &self,
+            _req: crate::core::permission::PermissionRequest,
+        ) -> PermissionDecision {
+            PermissionDecision::Allow
+        }
+        fn auto_approve_session(&self, _session_id: &str) {}
+    }
+
+    let tmp = tempfile::tempdir().unwrap();
+    let perm: Arc<dyn PermissionService> = Arc::new(AutoApprove);
+    let ctx = test_context(tmp.path());
+
+    let file_path = tmp.path().join("atomic.rs");
+    let original = "alpha\nbeta\nbeta\n";
+    std::fs::write(&file_path, original).unwrap();
+
+    let tool = super::MultiEditTool::new(perm);
+
+    // Second edit's old_string doesn't exist — nothing may change on disk
+    let call = ToolCall {
+        id: "1".into(),
+        name: "multi_edit".into(),
+        input: serde_json::json!({
+            "path": file_path.to_str().unwrap(),
+            "edits": [
+                {"old_string": "alpha", "new_string": "ALPHA"},
+                {"old_string": "missing", "new_string": "x"}
+            ]
+        })
+        .to_string(),
+    };
+    let result = tool.run(&call, &ctx).await.unwrap();
+    assert!(result.is_error);
+    assert!(result.content.contains("Edit 2 of 2"));
+    assert!(result.content.contains("not found"));
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), original);
+
+    // Ambiguous match without replace_all also fails without writing
+    let call = ToolCall {
+        id: "2".into(),
+        name: "multi_edit".into(),
+        input: serde_json::json!({
+            "path": file_path.to_str().unwrap(),
+            "edits": [
+                {"old_string": "beta", "new_string": "gamma"}
+            ]
+        })
+        .to_string(),
+    };
+    let result = tool.run(&call, &ctx).await.unwrap();
+    assert!(result.is_error);
+    assert!(result.content.contains("2 times"));
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), original);
+}
+
+#[test]
+fn test_tool_definition_serialization_is_deterministic() {
+    // The many-parameter coderlm definition is the most likely to expose
+    // unstable ordering
+    let tool = super::CoderlmTool::new(
+        "http://127.0.0.1:19999".into(),
+        &crate::core::config::HttpConfig::default(),
+    );
+    let def = tool.definition();
+
+    let first = serde_json::to_string(&def).unwrap();
+    let second = serde_json::to_string(&tool.definition()).unwrap();
+    assert_eq!(first, second);
+
+    // Parameter keys serialize in sorted order
+    let keys: Vec<&String> = def.parameters.keys().collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted);
+}
+
 #[tokio::test]
 async fn test_bash_safe_command() {
     use crate::core::permission::{PermissionDecision, PermissionService};
@@ -406,6 +731,64 @@ async fn test_bash_safe_command() {
     assert!(result.content.contains("hello world"));
 }
 
+#[tokio::test]
+async fn test_tools_report_affected_paths() {
+    use crate::core::permission::{PermissionDecision, PermissionService};
+    use std::sync::Arc;
+
+    struct AutoApprove;
+    #[async_trait::async_trait]
+    impl PermissionService for AutoApprove {
+        async fn request(
+            &self,
+            _req: crate::core::permission::PermissionRequest,
+        ) -> PermissionDecision {
+            PermissionDecision::Allow
+        }
+        fn auto_approve_session(&self, _session_id: &str) {}
+    }
+
+    let tmp = tempfile::tempdir().unwrap();
+    let perm: Arc<dyn PermissionService> = Arc::new(AutoApprove);
+    let ctx = test_context(tmp.path());
+
+    // write reports the exact path it touched
+    let write_tool = super::WriteTool::new(perm.clone());
+    let file_path = tmp.path().join("report.me.rs");
+    let call = ToolCall {
+        id: "1".into(),
+        name: "write".into(),
+        input: serde_json::json!({
+            "path": file_path.to_str().unwrap(),
+            "content": "fn f() {}\n"
+        })
+        .to_string(),
+    };
+    let result = write_tool.run(&call, &ctx).await.unwrap();
+    // Dotted filenames defeat the old result-string scraping
+    assert_eq!(result.affected_paths, vec![file_path.to_string_lossy().to_string()]);
+
+    // bash reports redirect targets and touch/mv-style arguments
+    let bash_tool = super::BashTool::new(perm);
+    let call = ToolCall {
+        id: "2".into(),
+        name: "bash".into(),
+        input: serde_json::json!({"command": "echo hi > out.txt && touch other.txt"})
+            .to_string(),
+    };
+    let result = bash_tool.run(&call, &ctx).await.unwrap();
+    assert_eq!(result.affected_paths, vec!["out.txt", "other.txt"]);
+
+    // read-only commands report nothing
+    let call = ToolCall {
+        id: "3".into(),
+        name: "bash".into(),
+        input: serde_json::json!({"command": "echo hello"}).to_string(),
+    };
+    let result = bash_tool.run(&call, &ctx).await.unwrap();
+    assert!(result.affected_paths.is_empty());
+}
+
 #[tokio::test]
 async fn test_bash_unsafe_command_denied() {
     use crate::core::permission::{PermissionDecision, PermissionService};
@@ -439,6 +822,90 @@ async fn test_bash_unsafe_command_denied() {
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_bash_cancellation_aborts_subprocess() {
+    use crate::core::error::ToolError;
+    use crate::core::permission::{PermissionDecision, PermissionService};
+    use std::sync::Arc;
+
+    struct AlwaysApprove;
+    #[async_trait::async_trait]
+    impl PermissionService for AlwaysApprove {
+        async fn request(
+            &self,
+            _req: crate::core::permission::PermissionRequest,
+        ) -> PermissionDecision {
+            PermissionDecision::Allow
+        }
+        fn auto_approve_session(&self, _session_id: &str) {}
+    }
+
+    let tmp = tempfile::tempdir().unwrap();
+    let perm: Arc<dyn PermissionService> = Arc::new(AlwaysApprove);
+    let ctx = test_context(tmp.path());
+    let token = ctx.cancel_token.clone();
+
+    let bash_tool = super::BashTool::new(perm);
+    let call = ToolCall {
+        id: "1".into(),
+        name: "bash".into(),
+        input: serde_json::json!({"command": "sleep 30"}).to_string(),
+    };
+
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        token.cancel();
+    });
+
+    let start = std::time::Instant::now();
+    let result = bash_tool.run(&call, &ctx).await;
+    assert!(matches!(result, Err(ToolError::Cancelled)));
+    // Prompt return, not the 30s the subprocess would have taken
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_coderlm_cancellation_aborts_request() {
+    use crate::core::error::ToolError;
+
+    // A server that accepts connections but never responds keeps the
+    // request in flight until the tool is cancelled
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((sock, _)) = listener.accept().await {
+            held.push(sock);
+        }
+    });
+
+    let tmp = tempfile::tempdir().unwrap();
+    let ctx = test_context(tmp.path());
+    let token = ctx.cancel_token.clone();
+
+    let tool = super::CoderlmTool::new(
+        format!("http://{addr}"),
+        &crate::core::config::HttpConfig::default(),
+    );
+    let call = ToolCall {
+        id: "1".into(),
+        name: "coderlm".into(),
+        input: serde_json::json!({"operation": "health"}).to_string(),
+    };
+
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        token.cancel();
+    });
+
+    let start = std::time::Instant::now();
+    let result = tool.run(&call, &ctx).await;
+    assert!(matches!(result, Err(ToolError::Cancelled)));
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    server.abort();
+}
+
 // -----------------------------------------------------------------------
 // Team tool tests
 // -----------------------------------------------------------------------
@@ -1,14 +1,19 @@
 use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct ViewTool;
 
+/// Cap on rendered line length. Line-based offset/limit doesn't help
+/// against a single enormous line (minified JS, a data blob), so anything
+/// longer is truncated with a note.
+const MAX_LINE_CHARS: usize = 2_000;
+
 #[async_trait]
 impl Tool for ViewTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "path".into(),
             ParamSchema {
@@ -106,9 +111,27 @@ impl Tool for ViewTool {
         let end = (start + limit).min(total_lines);
 
         let mut result = String::new();
+        let mut truncated_lines = 0usize;
         for (i, line) in lines[start..end].iter().enumerate() {
             let line_num = start + i + 1;
-            result.push_str(&format!("{:>6}\t{}\n", line_num, line));
+            if line.chars().count() > MAX_LINE_CHARS {
+                let cut: String = line.chars().take(MAX_LINE_CHARS).collect();
+                result.push_str(&format!(
+                    "{:>6}\t{}... [line truncated: {} chars total]\n",
+                    line_num,
+                    cut,
+                    line.chars().count()
+                ));
+                truncated_lines += 1;
+            } else {
+                result.push_str(&format!("{:>6}\t{}\n", line_num, line));
+            }
+        }
+
+        if truncated_lines > 0 {
+            result.push_str(&format!(
+                "\n({truncated_lines} line(s) exceeded {MAX_LINE_CHARS} chars and were truncated)"
+            ));
         }
 
         if end < total_lines {
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use crate::core::error::ToolError;
 use crate::core::permission::{PermissionDecision, PermissionRequest, PermissionService};
 use crate::core::tool::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 pub struct WriteTool {
@@ -18,7 +18,7 @@ impl WriteTool {
 #[async_trait]
 impl Tool for WriteTool {
     fn definition(&self) -> ToolDefinition {
-        let mut params = HashMap::new();
+        let mut params = BTreeMap::new();
         params.insert(
             "path".into(),
             ParamSchema {
@@ -87,15 +87,27 @@ impl Tool for WriteTool {
                 .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
         }
 
+        // Capture the previous content so the diff shows a new-file write
+        // as all-additions and a non-UTF-8 file as a binary summary
+        let old_content = match tokio::fs::read(&path).await {
+            Ok(bytes) => String::from_utf8(bytes).unwrap_or_else(|_| "\0".into()),
+            Err(_) => String::new(),
+        };
+
         tokio::fs::write(&path, content)
             .await
             .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
 
         let lines = content.lines().count();
-        Ok(ToolResult::success(format!(
+        let mut result = ToolResult::success(format!(
             "Wrote {} lines to {}",
             lines,
             path.display()
-        )))
+        ));
+        result.metadata = Some(serde_json::json!({
+            "path": path.to_string_lossy(),
+            "diff": crate::core::diff::unified_diff(&old_content, content),
+        }));
+        Ok(result.with_affected_paths(vec![path.to_string_lossy().to_string()]))
     }
 }